@@ -0,0 +1,59 @@
+use core::future::Future;
+
+use crate::{Error, Flow, LinkType, LinksConstants};
+
+/// Asynchronous counterpart of [`Links`](crate::Links), for storages whose
+/// I/O must not block the executor.
+///
+/// Each method mirrors the [`Links`](crate::Links) method of the same name,
+/// with the same queries, [`LinksConstants`], [`Flow`] and [`Error`]. Handlers
+/// are closures that return a future of [`Flow`], so a handler can await.
+/// A handler future cannot borrow the slices it is given; copy them first.
+///
+/// The futures are `Send`, so a call can be spawned on a multi-threaded
+/// runtime; in return, the storage, its link type, the handlers and their
+/// futures must be `Send` as well. With the `tokio` feature, `SpawnedLinks`
+/// turns any `Links` into an `AsyncLinks` and `BlockingLinks` does the
+/// opposite.
+pub trait AsyncLinks<T: LinkType + Send + Sync>: Send + Sync {
+    fn constants_links(&self) -> LinksConstants<T>;
+
+    fn count_links(&self, query: &[T]) -> impl Future<Output = T> + Send;
+
+    fn create_links<H, F>(
+        &mut self,
+        query: &[T],
+        handler: H,
+    ) -> impl Future<Output = Result<Flow, Error<'_, T>>> + Send
+    where
+        H: FnMut(&[T], &[T]) -> F + Send,
+        F: Future<Output = Flow> + Send;
+
+    fn each_links<H, F>(
+        &self,
+        query: &[T],
+        handler: H,
+    ) -> impl Future<Output = Result<Flow, Error<'_, T>>> + Send
+    where
+        H: FnMut(&[T]) -> F + Send,
+        F: Future<Output = Flow> + Send;
+
+    fn update_links<H, F>(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: H,
+    ) -> impl Future<Output = Result<Flow, Error<'_, T>>> + Send
+    where
+        H: FnMut(&[T], &[T]) -> F + Send,
+        F: Future<Output = Flow> + Send;
+
+    fn delete_links<H, F>(
+        &mut self,
+        query: &[T],
+        handler: H,
+    ) -> impl Future<Output = Result<Flow, Error<'_, T>>> + Send
+    where
+        H: FnMut(&[T], &[T]) -> F + Send,
+        F: Future<Output = Flow> + Send;
+}
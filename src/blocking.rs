@@ -0,0 +1,383 @@
+use std::{
+    future::{poll_fn, Future},
+    panic,
+    pin::pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::Poll,
+};
+
+use tokio::{
+    runtime::Handle,
+    sync::{mpsc, oneshot},
+    task,
+};
+
+use crate::{AsyncLinks, Error, Flow, LinkType, Links, LinksConstants, ReadHandler, WriteHandler};
+
+/// A handler call sent to the side that runs the handler, which answers with
+/// the handler's [`Flow`].
+struct Call<T> {
+    before: Vec<T>,
+    after: Vec<T>,
+    reply: oneshot::Sender<Flow>,
+}
+
+/// [`AsyncLinks`] over a synchronous [`Links`] storage, which runs on the
+/// blocking thread pool of Tokio.
+///
+/// The storage is locked for the duration of each call. Handlers run on the
+/// task that awaits the call, one link at a time, while the blocking thread
+/// waits for their [`Flow`], so a `Flow::Break` stops the storage. Dropping a
+/// call's future before it completes answers `Flow::Break` to the storage.
+///
+/// Calls must be made from within a Tokio runtime.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{AsyncLinks, Flow, MemoryLinks, SpawnedLinks};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let mut links = SpawnedLinks::new(MemoryLinks::<u64>::new());
+/// let mut created = 0;
+/// links
+///     .create_links(&[], |_, after| {
+///         created = after[0];
+///         async { Flow::Continue }
+///     })
+///     .await
+///     .unwrap();
+///
+/// let mut seen = vec![];
+/// links
+///     .each_links(&[], |link| {
+///         seen.push(link.to_vec());
+///         async { Flow::Continue }
+///     })
+///     .await
+///     .unwrap();
+/// assert_eq!(seen, [vec![created, 0, 0]]);
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct SpawnedLinks<T: LinkType, L> {
+    links: Arc<Mutex<L>>,
+    constants: LinksConstants<T>,
+}
+
+impl<T, L> SpawnedLinks<T, L>
+where
+    T: LinkType + Send + Sync,
+    L: Links<T> + Send + 'static,
+{
+    pub fn new(links: L) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links: Arc::new(Mutex::new(links)),
+        }
+    }
+
+    /// Returns the storage, or `None` while a dropped call still runs on a
+    /// blocking thread.
+    pub fn into_inner(self) -> Option<L> {
+        let links = Arc::try_unwrap(self.links).ok()?;
+        Some(links.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn lock(links: &Mutex<L>) -> MutexGuard<'_, L> {
+        // A panic in a previous call leaves the storage as the panic found it,
+        // as it would without the lock.
+        links.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs `operation` on the locked storage on a blocking thread.
+    async fn spawn<R: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&mut L) -> R + Send + 'static,
+    ) -> R {
+        let links = Arc::clone(&self.links);
+        task::spawn_blocking(move || operation(&mut Self::lock(&links)))
+            .await
+            .unwrap_or_else(|error| panic::resume_unwind(error.into_panic()))
+    }
+
+    /// Runs `operation` on a blocking thread, answering its handler calls
+    /// with `handler` on the current task.
+    async fn answer<H, F>(
+        &self,
+        operation: impl FnOnce(&mut L, WriteHandler<'_, T>) -> Result<Flow, Error<'static, T>>
+            + Send
+            + 'static,
+        mut handler: H,
+    ) -> Result<Flow, Error<'static, T>>
+    where
+        H: FnMut(&[T], &[T]) -> F + Send,
+        F: Future<Output = Flow> + Send,
+    {
+        let links = Arc::clone(&self.links);
+        let (sender, mut calls) = mpsc::channel(1);
+        let task = task::spawn_blocking(move || {
+            operation(&mut Self::lock(&links), &mut |before, after| {
+                let (reply, flow) = oneshot::channel();
+                let call = Call {
+                    before: before.to_vec(),
+                    after: after.to_vec(),
+                    reply,
+                };
+                match sender.blocking_send(call) {
+                    Ok(()) => flow.blocking_recv().unwrap_or(Flow::Break),
+                    Err(_) => Flow::Break,
+                }
+            })
+        });
+        while let Some(call) = calls.recv().await {
+            let Call {
+                before,
+                after,
+                reply,
+            } = call;
+            let _ = reply.send(handler(&before, &after).await);
+        }
+        task.await
+            .unwrap_or_else(|error| panic::resume_unwind(error.into_panic()))
+    }
+}
+
+impl<T, L> AsyncLinks<T> for SpawnedLinks<T, L>
+where
+    T: LinkType + Send + Sync,
+    L: Links<T> + Send + 'static,
+{
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    async fn count_links(&self, query: &[T]) -> T {
+        let query = query.to_vec();
+        self.spawn(move |links| links.count_links(&query)).await
+    }
+
+    async fn create_links<H, F>(&mut self, query: &[T], handler: H) -> Result<Flow, Error<'_, T>>
+    where
+        H: FnMut(&[T], &[T]) -> F + Send,
+        F: Future<Output = Flow> + Send,
+    {
+        let query = query.to_vec();
+        self.answer(
+            move |links, handler| {
+                links
+                    .create_links(&query, handler)
+                    .map_err(Error::into_owned)
+            },
+            handler,
+        )
+        .await
+    }
+
+    async fn each_links<H, F>(&self, query: &[T], mut handler: H) -> Result<Flow, Error<'_, T>>
+    where
+        H: FnMut(&[T]) -> F + Send,
+        F: Future<Output = Flow> + Send,
+    {
+        let query = query.to_vec();
+        self.answer(
+            move |links, handler| {
+                links
+                    .each_links(&query, &mut |link| handler(link, &[]))
+                    .map_err(Error::into_owned)
+            },
+            |link, _| handler(link),
+        )
+        .await
+    }
+
+    async fn update_links<H, F>(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: H,
+    ) -> Result<Flow, Error<'_, T>>
+    where
+        H: FnMut(&[T], &[T]) -> F + Send,
+        F: Future<Output = Flow> + Send,
+    {
+        let (query, replacement) = (query.to_vec(), replacement.to_vec());
+        self.answer(
+            move |links, handler| {
+                links
+                    .update_links(&query, &replacement, handler)
+                    .map_err(Error::into_owned)
+            },
+            handler,
+        )
+        .await
+    }
+
+    async fn delete_links<H, F>(&mut self, query: &[T], handler: H) -> Result<Flow, Error<'_, T>>
+    where
+        H: FnMut(&[T], &[T]) -> F + Send,
+        F: Future<Output = Flow> + Send,
+    {
+        let query = query.to_vec();
+        self.answer(
+            move |links, handler| {
+                links
+                    .delete_links(&query, handler)
+                    .map_err(Error::into_owned)
+            },
+            handler,
+        )
+        .await
+    }
+}
+
+/// Blocking [`Links`] over an [`AsyncLinks`] storage, which awaits each call
+/// on a Tokio runtime.
+///
+/// Handlers run synchronously on the calling thread while the storage awaits
+/// their [`Flow`], so a `Flow::Break` stops the storage. Like
+/// [`Handle::block_on`], the methods panic when called from within an
+/// asynchronous context; call them from a plain thread or from
+/// [`spawn_blocking`](tokio::task::spawn_blocking).
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{BlockingLinks, LinksExt, MemoryLinks, SpawnedLinks};
+///
+/// let runtime = tokio::runtime::Runtime::new().unwrap();
+/// let storage = runtime.block_on(async { SpawnedLinks::new(MemoryLinks::<u64>::new()) });
+/// let mut links = BlockingLinks::new(storage, runtime.handle().clone());
+///
+/// let a = links.create_link(100, 200).unwrap();
+/// assert_eq!(links.search(100, 200), Some(a));
+/// ```
+#[derive(Debug)]
+pub struct BlockingLinks<T: LinkType, A> {
+    links: A,
+    handle: Handle,
+    constants: LinksConstants<T>,
+}
+
+impl<T: LinkType + Send + Sync, A: AsyncLinks<T>> BlockingLinks<T, A> {
+    pub fn new(links: A, handle: Handle) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links,
+            handle,
+        }
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.links
+    }
+
+    pub fn into_inner(self) -> A {
+        self.links
+    }
+}
+
+/// Returns a handler future that sends the call to `sender` and resolves to
+/// the answer, or to `Flow::Break` once nobody answers.
+fn forward<T: LinkType + Send>(
+    sender: &mpsc::Sender<Call<T>>,
+    before: &[T],
+    after: &[T],
+) -> impl Future<Output = Flow> + Send {
+    let sender = sender.clone();
+    let (reply, flow) = oneshot::channel();
+    let call = Call {
+        before: before.to_vec(),
+        after: after.to_vec(),
+        reply,
+    };
+    async move {
+        match sender.send(call).await {
+            Ok(()) => flow.await.unwrap_or(Flow::Break),
+            Err(_) => Flow::Break,
+        }
+    }
+}
+
+/// Blocks on `call`, answering the handler calls it sends to `calls` with
+/// `handler` on the current thread.
+fn answer<T, R>(
+    handle: &Handle,
+    call: impl Future<Output = R>,
+    mut calls: mpsc::Receiver<Call<T>>,
+    handler: WriteHandler<'_, T>,
+) -> R {
+    let mut call = pin!(call);
+    handle.block_on(poll_fn(|context| loop {
+        if let Poll::Ready(result) = call.as_mut().poll(context) {
+            return Poll::Ready(result);
+        }
+        match calls.poll_recv(context) {
+            Poll::Ready(Some(Call {
+                before,
+                after,
+                reply,
+            })) => {
+                let _ = reply.send(handler(&before, &after));
+            }
+            _ => return Poll::Pending,
+        }
+    }))
+}
+
+impl<T: LinkType + Send + Sync, A: AsyncLinks<T>> Links<T> for BlockingLinks<T, A> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.handle.block_on(self.links.count_links(query))
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let (sender, calls) = mpsc::channel(1);
+        let call = self
+            .links
+            .create_links(query, move |before, after| forward(&sender, before, after));
+        answer(&self.handle, call, calls, handler)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        let (sender, calls) = mpsc::channel(1);
+        let call = self
+            .links
+            .each_links(query, move |link| forward(&sender, link, &[]));
+        answer(&self.handle, call, calls, &mut |link, _| handler(link))
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let (sender, calls) = mpsc::channel(1);
+        let call = self
+            .links
+            .update_links(query, replacement, move |before, after| {
+                forward(&sender, before, after)
+            });
+        answer(&self.handle, call, calls, handler)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let (sender, calls) = mpsc::channel(1);
+        let call = self
+            .links
+            .delete_links(query, move |before, after| forward(&sender, before, after));
+        answer(&self.handle, call, calls, handler)
+    }
+}
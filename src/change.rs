@@ -0,0 +1,77 @@
+use crate::{Error, Flow, Link, LinkType, Links, LinksConstants, WriteHandler};
+
+/// A single mutation, as reported to a [`WriteHandler`].
+///
+/// A creation has [`Link::nothing`] as `before`, and a deletion has it as `after`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Change<T> {
+    pub before: Link<T>,
+    pub after: Link<T>,
+}
+
+impl<T: LinkType> Change<T> {
+    pub const fn new(before: Link<T>, after: Link<T>) -> Self {
+        Self { before, after }
+    }
+
+    /// Reads the slices that a [`WriteHandler`] receives.
+    pub fn from_slices(before: &[T], after: &[T], constants: &LinksConstants<T>) -> Self {
+        Self::new(
+            Link::from_slice(before, constants),
+            Link::from_slice(after, constants),
+        )
+    }
+
+    pub fn is_create(&self) -> bool {
+        self.before.is_nothing() && !self.after.is_nothing()
+    }
+
+    pub fn is_update(&self) -> bool {
+        !self.before.is_nothing() && !self.after.is_nothing()
+    }
+
+    pub fn is_delete(&self) -> bool {
+        !self.before.is_nothing() && self.after.is_nothing()
+    }
+
+    /// Returns the change that undoes this one.
+    pub fn inverse(&self) -> Self {
+        Self::new(self.after, self.before)
+    }
+
+    /// Makes the same change to `links`, which must hold `before`.
+    ///
+    /// A creation takes the address of `after`, so the storage has to support
+    /// creating links at a chosen address, and a created link with a source or
+    /// target is reported to `handler` as a creation followed by an update.
+    pub fn apply<'a, L: Links<T> + ?Sized>(
+        &self,
+        links: &'a mut L,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'a, T>> {
+        let constants = links.constants_links();
+        let Link {
+            index,
+            source,
+            target,
+        } = self.after;
+
+        if self.after.is_nothing() {
+            return links.delete_links(&[self.before.index], handler);
+        }
+        if self.before.is_nothing() {
+            let flow = links
+                .create_links(&[index], handler)
+                .map_err(Error::into_owned)?;
+            if source == constants.null && target == constants.null {
+                return Ok(flow);
+            }
+            let replacement = self.after.to_vec(&constants);
+            return match links.update_links(&[index], &replacement, handler)? {
+                Flow::Continue => Ok(flow),
+                Flow::Break => Ok(Flow::Break),
+            };
+        }
+        links.update_links(&[index], &self.after.to_vec(&constants), handler)
+    }
+}
@@ -1,8 +1,9 @@
-use std::ops::RangeInclusive;
+use core::ops::RangeInclusive;
 
 use crate::{Hybrid, LinkType};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LinksConstants<T: LinkType> {
     pub index_part: T,
     pub source_part: T,
@@ -23,6 +24,8 @@ impl<T: LinkType> LinksConstants<T> {
         T::funty(2)
     }
 
+    /// Derives the reserved values from the end of `internal` without checking
+    /// the layout; [`builder`](Self::builder) checks it.
     pub fn full_new(
         target_part: T,
         internal: RangeInclusive<T>,
@@ -73,6 +76,14 @@ impl<T: LinkType> LinksConstants<T> {
         Self::internal()
     }
 
+    /// Starts a layout equal to [`new`](Self::new), to be adjusted and checked.
+    pub fn builder() -> LinksConstantsBuilder<T> {
+        LinksConstantsBuilder::new()
+    }
+
+    // With an external range, the split follows `HybridKind`: internal
+    // addresses are the positive hybrids and external references the negative
+    // ones.
     fn default_internal(external: bool) -> RangeInclusive<T> {
         if external {
             T::funty(1)..=Hybrid::half()
@@ -83,7 +94,7 @@ impl<T: LinkType> LinksConstants<T> {
 
     fn default_external(external: bool) -> Option<RangeInclusive<T>> {
         if external {
-            Some(Hybrid::half()..=T::MAX)
+            Some(Hybrid::<T>::half() + T::funty(1)..=T::MAX)
         } else {
             None
         }
@@ -102,6 +113,20 @@ impl<T: LinkType> LinksConstants<T> {
     pub fn is_reference(&self, address: T) -> bool {
         self.is_internal(address) || self.is_external(address)
     }
+
+    /// Returns `true` for the values with a special meaning in queries and
+    /// handlers, such as `any` or `break`, which are never link addresses.
+    pub fn is_reserved(&self, value: T) -> bool {
+        [
+            self.r#continue,
+            self.r#break,
+            self.skip,
+            self.any,
+            self.itself,
+            self.error,
+        ]
+        .contains(&value)
+    }
 }
 
 impl<T: LinkType> Default for LinksConstants<T> {
@@ -109,3 +134,291 @@ impl<T: LinkType> Default for LinksConstants<T> {
         Self::new()
     }
 }
+
+/// Inconsistency found by [`LinksConstantsBuilder::build`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConstantsError<T: LinkType> {
+    #[error("index part {index} and source part {source} are not 0 and 1")]
+    PartLayout { index: T, source: T },
+
+    #[error("target part {0} leaves no room for the index and source parts")]
+    TargetPart(T),
+
+    #[error("range {start}..={end} is empty")]
+    EmptyRange { start: T, end: T },
+
+    #[error(
+        "internal range {start}..={end} is too small to hold the reserved values and an address"
+    )]
+    TooSmall { start: T, end: T },
+
+    #[error("internal range {internal:?} overlaps external range {external:?}")]
+    Overlap {
+        internal: RangeInclusive<T>,
+        external: RangeInclusive<T>,
+    },
+
+    #[error("null {0} is not zero")]
+    NonZeroNull(T),
+
+    #[error("null {0} is also an address")]
+    NullInRange(T),
+
+    #[error("reserved value {0} is used twice")]
+    DuplicateReserved(T),
+
+    #[error("reserved value {0} is also an internal address")]
+    ReservedInRange(T),
+}
+
+/// Builder for [`LinksConstants`] layouts that [`full_new`](LinksConstants::full_new)
+/// cannot express, checked by [`build`](Self::build).
+///
+/// Reserved values that are not set are taken from the end of the internal
+/// range, in the order `continue`, `break`, `skip`, `any`, `itself`, `error`,
+/// and the internal range shrinks accordingly. Without any setter, the
+/// builder yields [`LinksConstants::new`].
+///
+/// Reserved values take precedence over external references, so they may fall
+/// in the external range. The index and source parts must stay at 0 and 1,
+/// since single-value queries such as `[index]` always name the index first,
+/// and `null` must stay zero, since the storages clear links with it. The
+/// target part may move further out.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{ConstantsError, LinksConstants};
+///
+/// let constants = LinksConstants::<u32>::builder()
+///     .internal_range(1..=1000)
+///     .external_range(2000..=u32::MAX)
+///     .any(1999)
+///     .build()
+///     .unwrap();
+/// assert_eq!(constants.any, 1999);
+/// assert_eq!(constants.r#continue, 1000);
+/// assert_eq!(constants.internal_range, 1..=995);
+///
+/// let overlapping = LinksConstants::<u32>::builder()
+///     .internal_range(1..=1000)
+///     .external_range(500..=u32::MAX)
+///     .build();
+/// assert!(matches!(overlapping, Err(ConstantsError::Overlap { .. })));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LinksConstantsBuilder<T: LinkType> {
+    index_part: T,
+    source_part: T,
+    target_part: T,
+    null: T,
+    reserved: [Option<T>; 6],
+    internal_range: RangeInclusive<T>,
+    external_range: Option<RangeInclusive<T>>,
+}
+
+const CONTINUE: usize = 0;
+const BREAK: usize = 1;
+const SKIP: usize = 2;
+const ANY: usize = 3;
+const ITSELF: usize = 4;
+const ERROR: usize = 5;
+
+impl<T: LinkType> LinksConstantsBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            index_part: T::funty(0),
+            source_part: T::funty(1),
+            target_part: T::funty(2),
+            null: T::funty(0),
+            reserved: [None; 6],
+            internal_range: T::funty(1)..=T::MAX,
+            external_range: None,
+        }
+    }
+
+    pub fn index_part(mut self, part: T) -> Self {
+        self.index_part = part;
+        self
+    }
+
+    pub fn source_part(mut self, part: T) -> Self {
+        self.source_part = part;
+        self
+    }
+
+    pub fn target_part(mut self, part: T) -> Self {
+        self.target_part = part;
+        self
+    }
+
+    pub fn null(mut self, value: T) -> Self {
+        self.null = value;
+        self
+    }
+
+    pub fn r#continue(mut self, value: T) -> Self {
+        self.reserved[CONTINUE] = Some(value);
+        self
+    }
+
+    pub fn r#break(mut self, value: T) -> Self {
+        self.reserved[BREAK] = Some(value);
+        self
+    }
+
+    pub fn skip(mut self, value: T) -> Self {
+        self.reserved[SKIP] = Some(value);
+        self
+    }
+
+    pub fn any(mut self, value: T) -> Self {
+        self.reserved[ANY] = Some(value);
+        self
+    }
+
+    pub fn itself(mut self, value: T) -> Self {
+        self.reserved[ITSELF] = Some(value);
+        self
+    }
+
+    pub fn error(mut self, value: T) -> Self {
+        self.reserved[ERROR] = Some(value);
+        self
+    }
+
+    /// Sets the internal range, including the values reserved from its end.
+    pub fn internal_range(mut self, range: RangeInclusive<T>) -> Self {
+        self.internal_range = range;
+        self
+    }
+
+    pub fn external_range(mut self, range: RangeInclusive<T>) -> Self {
+        self.external_range = Some(range);
+        self
+    }
+
+    pub fn build(self) -> Result<LinksConstants<T>, ConstantsError<T>> {
+        if (self.index_part, self.source_part) != (T::funty(0), T::funty(1)) {
+            return Err(ConstantsError::PartLayout {
+                index: self.index_part,
+                source: self.source_part,
+            });
+        }
+        if self.target_part < T::funty(2) {
+            return Err(ConstantsError::TargetPart(self.target_part));
+        }
+        if self.null != T::funty(0) {
+            return Err(ConstantsError::NonZeroNull(self.null));
+        }
+
+        let (start, mut end) = (*self.internal_range.start(), *self.internal_range.end());
+        if start > end {
+            return Err(ConstantsError::EmptyRange { start, end });
+        }
+        let too_small = ConstantsError::TooSmall { start, end };
+        let mut reserved = [T::funty(0); 6];
+        for (value, set) in reserved.iter_mut().zip(self.reserved) {
+            *value = match set {
+                Some(set) => set,
+                None if end > start => {
+                    let value = end;
+                    end -= T::funty(1);
+                    value
+                }
+                None => return Err(too_small),
+            };
+        }
+        let internal = start..=end;
+
+        if let Some(external) = &self.external_range {
+            if external.start() > external.end() {
+                return Err(ConstantsError::EmptyRange {
+                    start: *external.start(),
+                    end: *external.end(),
+                });
+            }
+            if external.start() <= internal.end() && internal.start() <= external.end() {
+                return Err(ConstantsError::Overlap {
+                    internal,
+                    external: external.clone(),
+                });
+            }
+        }
+        let external = self.external_range.as_ref();
+        if internal.contains(&self.null) || external.is_some_and(|range| range.contains(&self.null))
+        {
+            return Err(ConstantsError::NullInRange(self.null));
+        }
+        for (i, value) in reserved.iter().enumerate() {
+            if *value == self.null || reserved[..i].contains(value) {
+                return Err(ConstantsError::DuplicateReserved(*value));
+            }
+            if internal.contains(value) {
+                return Err(ConstantsError::ReservedInRange(*value));
+            }
+        }
+
+        Ok(LinksConstants {
+            index_part: self.index_part,
+            source_part: self.source_part,
+            target_part: self.target_part,
+            null: self.null,
+            r#continue: reserved[CONTINUE],
+            r#break: reserved[BREAK],
+            skip: reserved[SKIP],
+            any: reserved[ANY],
+            itself: reserved[ITSELF],
+            error: reserved[ERROR],
+            internal_range: internal,
+            external_range: self.external_range,
+        })
+    }
+}
+
+impl<T: LinkType> Default for LinksConstantsBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! const_constants {
+    ($($ty:ty),*) => {$(
+        impl LinksConstants<$ty> {
+            /// Same as [`internal`](Self::internal), usable in `const` items and patterns.
+            pub const INTERNAL: Self = Self::full_new_const(2, 1..=<$ty>::MAX, None);
+
+            /// Same as [`external`](Self::external), usable in `const` items and patterns.
+            pub const EXTERNAL: Self = Self::full_new_const(
+                2,
+                1..=Hybrid::<$ty>::HALF,
+                Some(Hybrid::<$ty>::HALF + 1..=<$ty>::MAX),
+            );
+
+            /// Same as [`full_new`](Self::full_new), evaluable at compile time.
+            pub const fn full_new_const(
+                target_part: $ty,
+                internal: RangeInclusive<$ty>,
+                external: Option<RangeInclusive<$ty>>,
+            ) -> Self {
+                let (start, end) = (*internal.start(), *internal.end());
+                Self {
+                    index_part: 0,
+                    source_part: 1,
+                    target_part,
+                    null: 0,
+                    r#continue: end,
+                    r#break: end - 1,
+                    skip: end - 2,
+                    any: end - 3,
+                    itself: end - 4,
+                    error: end - 5,
+                    internal_range: RangeInclusive::new(start, end - 6),
+                    external_range: external,
+                }
+            }
+        }
+    )*};
+}
+
+const_constants!(u8, u16, u32, u64, u128, usize);
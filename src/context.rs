@@ -0,0 +1,140 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{error, Error, ErrorKind, LinkType};
+
+/// The [`Links`](crate::Links) method that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Count,
+    Create,
+    Each,
+    Update,
+    Delete,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Count => "count",
+            Operation::Create => "create",
+            Operation::Each => "each",
+            Operation::Update => "update",
+            Operation::Delete => "delete",
+        })
+    }
+}
+
+/// An [`Error`] that owns its data, with the context it happened in.
+///
+/// Unlike `Error<'_, T>`, it does not borrow the storage, so it can be
+/// returned with `?` past the call that failed, kept, or boxed into any error
+/// type that requires `'static`. Build one with
+/// [`Error::context`] to record the operation and the query, or with `From`
+/// when they are not known.
+///
+/// Its message only names the failed operation and query; the wrapped error
+/// is its [`source`](core::error::Error::source), so reporters that print the
+/// chain of sources show it once.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{ErrorKind, LinksError, LinksExt, MemoryLinks, Operation};
+///
+/// fn delete_twice(links: &mut MemoryLinks<u64>, index: u64) -> Result<(), LinksError<u64>> {
+///     links.delete(index)?;
+///     links
+///         .delete(index)
+///         .map_err(|error| error.context(Operation::Delete, &[index]))?;
+///     Ok(())
+/// }
+///
+/// let mut links = MemoryLinks::new();
+/// let a = links.create().unwrap();
+/// let error = delete_twice(&mut links, a).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::NotExists);
+/// assert_eq!(error.operation(), Some(Operation::Delete));
+/// assert_eq!(error.query(), [a]);
+/// assert_eq!(error.to_string(), "delete [1] failed");
+/// ```
+#[derive(Debug)]
+pub struct LinksError<T: LinkType> {
+    operation: Option<Operation>,
+    query: Vec<T>,
+    found: Option<T>,
+    error: Error<'static, T>,
+}
+
+impl<T: LinkType> LinksError<T> {
+    pub fn kind(&self) -> ErrorKind {
+        self.error.kind()
+    }
+
+    /// Returns the operation that failed, if it was recorded.
+    pub fn operation(&self) -> Option<Operation> {
+        self.operation
+    }
+
+    /// Returns the query of the failed operation, empty if it was not recorded.
+    pub fn query(&self) -> &[T] {
+        &self.query
+    }
+
+    /// Returns the address of the link that was found in the way, such as the
+    /// existing link of [`Error::AlreadyExists`].
+    pub fn found(&self) -> Option<T> {
+        self.found
+    }
+
+    /// Records the address of the link that was found in the way.
+    pub fn with_found(mut self, found: T) -> Self {
+        self.found = Some(found);
+        self
+    }
+
+    /// Records the operation that failed and its query.
+    pub fn with_operation(mut self, operation: Operation, query: &[T]) -> Self {
+        self.operation = Some(operation);
+        self.query = query.to_vec();
+        self
+    }
+
+    pub fn get_ref(&self) -> &Error<'static, T> {
+        &self.error
+    }
+
+    pub fn into_inner(self) -> Error<'static, T> {
+        self.error
+    }
+}
+
+impl<T: LinkType> From<Error<'_, T>> for LinksError<T> {
+    fn from(error: Error<'_, T>) -> Self {
+        let found = match &error {
+            Error::AlreadyExists(index) => Some(**index),
+            _ => None,
+        };
+        Self {
+            operation: None,
+            query: Vec::new(),
+            found,
+            error: error.into_owned(),
+        }
+    }
+}
+
+impl<T: LinkType> fmt::Display for LinksError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.operation {
+            Some(operation) => write!(f, "{} {:?} failed", operation, self.query),
+            None => f.write_str("links operation failed"),
+        }
+    }
+}
+
+impl<T: LinkType> error::Error for LinksError<T> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
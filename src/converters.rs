@@ -1,5 +1,9 @@
 use crate::{Hybrid, LinkType};
 
+/// Turns an address into the raw value of an external reference to it.
+///
+/// Addresses `1..=half() + 1` become [external](crate::HybridKind::External) values and
+/// `0` stays null; larger addresses have no external reference.
 #[derive(Default)]
 pub struct AddrToRaw;
 
@@ -9,11 +13,13 @@ impl AddrToRaw {
     }
 }
 
+/// Turns the raw value of an external reference back into its address,
+/// undoing [`AddrToRaw`] for every [external](crate::HybridKind::External) value.
 #[derive(Default)]
 pub struct RawToAddr;
 
 impl RawToAddr {
     pub fn convert<T: LinkType>(&self, source: T) -> T {
-        Hybrid::external(source).abs()
+        Hybrid::new(source).abs()
     }
 }
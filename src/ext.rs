@@ -0,0 +1,227 @@
+use alloc::vec::Vec;
+
+use crate::{Error, Flow, FlowWith, Link, LinkType, Links, LinksIter};
+
+/// Convenience methods for single links, available on every [`Links`] storage.
+///
+/// Each helper is built only on the six required [`Links`] methods, so it
+/// works the same way on every storage.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{LinksExt, MemoryLinks};
+///
+/// let mut links = MemoryLinks::<usize>::new();
+/// let a = links.create_link(1, 1).unwrap();
+/// let b = links.create().unwrap();
+/// links.update(b, a, b).unwrap();
+///
+/// assert_eq!(links.search(a, b), Some(b));
+/// assert_eq!(links.get_link(b).map(|link| link.source), Some(a));
+/// ```
+pub trait LinksExt<T: LinkType>: Links<T> {
+    /// Creates an empty link and returns its address.
+    fn create(&mut self) -> Result<T, Error<'_, T>> {
+        let constants = self.constants_links();
+        let mut index = constants.null;
+        self.create_links(&[], &mut |_, after| {
+            index = after[constants.index_part.as_usize()];
+            Flow::Continue
+        })?;
+        Ok(index)
+    }
+
+    /// Creates a link from `source` to `target` and returns its address.
+    ///
+    /// The link is created empty and then updated; if the update fails, the
+    /// empty link is deleted again before the error is returned.
+    fn create_link(&mut self, source: T, target: T) -> Result<T, Error<'_, T>> {
+        let index = self.create().map_err(Error::into_owned)?;
+        match self
+            .update(index, source, target)
+            .map_err(Error::into_owned)
+        {
+            Ok(index) => Ok(index),
+            Err(error) => {
+                let _ = self.delete(index);
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns the link at `index`, if it exists.
+    ///
+    /// `any` and other values outside the internal range name no link.
+    fn get_link(&self, index: T) -> Option<Link<T>> {
+        let constants = self.constants_links();
+        if index == constants.any || !constants.is_internal(index) {
+            return None;
+        }
+        self.each_with(&[index], |link| {
+            FlowWith::Break(Link::from_slice(link, &constants))
+        })
+        .ok()?
+        .break_value()
+    }
+
+    /// Returns `true` if a link exists at `index`, which is never the case
+    /// for `any` or other values outside the internal range.
+    fn exists(&self, index: T) -> bool {
+        let constants = self.constants_links();
+        index != constants.any
+            && constants.is_internal(index)
+            && self.count_links(&[index]) != T::funty(0)
+    }
+
+    /// Points the link at `index` to `source` and `target`, returning `index`.
+    fn update(&mut self, index: T, source: T, target: T) -> Result<T, Error<'_, T>> {
+        let constants = self.constants_links();
+        let query = [index];
+        let replacement = Link::new(index, source, target).to_vec(&constants);
+        self.update_links(&query, &replacement, &mut |_, _| Flow::Continue)?;
+        Ok(index)
+    }
+
+    /// Deletes the link at `index`, returning `index`.
+    fn delete(&mut self, index: T) -> Result<T, Error<'_, T>> {
+        self.delete_links(&[index], &mut |_, _| Flow::Continue)?;
+        Ok(index)
+    }
+
+    /// Returns the address of a link from `source` to `target`, if there is one.
+    fn search(&self, source: T, target: T) -> Option<T> {
+        let constants = self.constants_links();
+        let query = Link::new(constants.any, source, target).to_vec(&constants);
+        self.each_with(&query, |link| {
+            FlowWith::Break(link[constants.index_part.as_usize()])
+        })
+        .ok()?
+        .break_value()
+    }
+
+    /// Like [`Links::each_links`], but returns the value the handler breaks
+    /// with.
+    ///
+    /// The value does not pass through the storage, which only sees a bare
+    /// [`Flow`]: the result is the first break of the handler, which is not
+    /// called again after it, even if the storage goes on or returns
+    /// `Flow::Continue`, and `FlowWith::Continue` if the handler never broke.
+    ///
+    /// ```
+    /// use platform_data::{FlowWith, LinksExt, MemoryLinks};
+    ///
+    /// let mut links = MemoryLinks::<usize>::new();
+    /// let a = links.create_link(100, 100).unwrap();
+    /// let b = links.create_link(a, a).unwrap();
+    ///
+    /// let found = links.each_with(&[], |link| {
+    ///     if link[1] == a { FlowWith::Break(link[0]) } else { FlowWith::Continue }
+    /// });
+    /// assert_eq!(found.unwrap(), FlowWith::Break(b));
+    /// ```
+    fn each_with<B>(
+        &self,
+        query: &[T],
+        mut handler: impl FnMut(&[T]) -> FlowWith<B>,
+    ) -> Result<FlowWith<B>, Error<'_, T>> {
+        let mut value = None;
+        self.each_links(query, &mut |link| keep(&mut value, || handler(link)))?;
+        Ok(value.into())
+    }
+
+    /// Like [`Links::create_links`], but returns the value the handler breaks
+    /// with, as [`each_with`](Self::each_with) does.
+    fn create_with<B>(
+        &mut self,
+        query: &[T],
+        mut handler: impl FnMut(&[T], &[T]) -> FlowWith<B>,
+    ) -> Result<FlowWith<B>, Error<'_, T>> {
+        let mut value = None;
+        self.create_links(query, &mut |before, after| {
+            keep(&mut value, || handler(before, after))
+        })?;
+        Ok(value.into())
+    }
+
+    /// Like [`Links::update_links`], but returns the value the handler breaks
+    /// with, as [`each_with`](Self::each_with) does.
+    fn update_with<B>(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        mut handler: impl FnMut(&[T], &[T]) -> FlowWith<B>,
+    ) -> Result<FlowWith<B>, Error<'_, T>> {
+        let mut value = None;
+        self.update_links(query, replacement, &mut |before, after| {
+            keep(&mut value, || handler(before, after))
+        })?;
+        Ok(value.into())
+    }
+
+    /// Like [`Links::delete_links`], but returns the value the handler breaks
+    /// with, as [`each_with`](Self::each_with) does.
+    fn delete_with<B>(
+        &mut self,
+        query: &[T],
+        mut handler: impl FnMut(&[T], &[T]) -> FlowWith<B>,
+    ) -> Result<FlowWith<B>, Error<'_, T>> {
+        let mut value = None;
+        self.delete_links(query, &mut |before, after| {
+            keep(&mut value, || handler(before, after))
+        })?;
+        Ok(value.into())
+    }
+
+    /// Returns the links other than `index` itself whose source or target is
+    /// `index`, the ones that keep it from being deleted.
+    fn usages(&self, index: T) -> Vec<Link<T>> {
+        let constants = self.constants_links();
+        let mut usages = Vec::new();
+        let _ = self.each_links(&[constants.any, index], &mut |link| {
+            let link = Link::from_slice(link, &constants);
+            if link.index != index {
+                usages.push(link);
+            }
+            Flow::Continue
+        });
+        usages
+    }
+
+    /// Returns an iterator over the links that match `query`.
+    ///
+    /// The iterator yields the same links as [`Links::each_links`], and as
+    /// many as [`Links::count_links`] reports for the same query.
+    ///
+    /// ```
+    /// use platform_data::{Link, LinksExt, MemoryLinks};
+    ///
+    /// let mut links = MemoryLinks::<usize>::new();
+    /// for _ in 0..3 {
+    ///     links.create_link(1, 1).unwrap();
+    /// }
+    ///
+    /// let indices: Vec<_> = links.iter(&[]).unwrap().map(|link| link.index).collect();
+    /// assert_eq!(indices, [1, 2, 3]);
+    /// ```
+    fn iter(&self, query: &[T]) -> Result<LinksIter<T>, Error<'_, T>> {
+        LinksIter::new(self, query)
+    }
+
+    /// Returns the number of links in the storage.
+    fn count_all(&self) -> T {
+        self.count_links(&[])
+    }
+}
+
+impl<T: LinkType, L: Links<T> + ?Sized> LinksExt<T> for L {}
+
+/// Calls `handler` until it breaks and keeps its break value in `value`, so
+/// a storage that goes on after a break gets `Flow::Break` again instead.
+fn keep<B>(value: &mut Option<B>, handler: impl FnOnce() -> FlowWith<B>) -> Flow {
+    if value.is_some() {
+        Flow::Break
+    } else {
+        handler().store(value)
+    }
+}
@@ -0,0 +1,538 @@
+use std::{
+    borrow::Cow,
+    fs::{File, OpenOptions},
+    io, mem,
+    path::Path,
+    slice,
+};
+
+use memmap2::MmapMut;
+
+use crate::{
+    store::Store, Error, Flow, LinkType, Links, LinksConstants, ReadHandler, SizeBalancedTree,
+    TreeNode, TreeNodes, TreeNodesMut, WriteHandler,
+};
+
+/// Number of link records a new file is created with.
+const INITIAL_CAPACITY: usize = 1024;
+
+/// Marks a file as written by [`FileLinks`].
+const MAGIC: u8 = b'L';
+
+/// Largest number of unused records an explicit create index may skip, since
+/// each of them is put on the free list.
+const MAX_GAP: usize = 1 << 20;
+
+/// Link record as laid out in the file.
+///
+/// A free record has `sources.size == 0` and uses `sources.left` and
+/// `sources.right` as the previous and next entries of the free list.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Record<T> {
+    source: T,
+    target: T,
+    sources: TreeNode<T>,
+    targets: TreeNode<T>,
+}
+
+/// First block of the file, the same size as a [`Record`].
+///
+/// `reserved` holds [`MAGIC`], the size of the link type and the start of the
+/// internal range, which opening the file checks.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+struct Header<T> {
+    allocated: T,
+    free: T,
+    count: T,
+    source_root: T,
+    target_root: T,
+    reserved: [T; 3],
+}
+
+/// [`Links`] storage kept in a memory-mapped file.
+///
+/// The file holds a header followed by link records that embed the nodes of
+/// the source and target [`SizeBalancedTree`]s, so a store reopened from the
+/// same file continues where the previous process stopped. The file grows as
+/// links are created, and failures to grow or map it are reported as
+/// [`Error::AllocFailed`].
+///
+/// Query semantics and address allocation match [`MemoryLinks`](crate::MemoryLinks),
+/// except that freed addresses are reused most recent first. The file records
+/// the size of its link type and the start of its internal range, and the rest
+/// of its [`LinksConstants`] must stay the same between opens. An explicit create index may skip at most about a million unused addresses.
+///
+/// The records are accessed through the mapping, so the file must not be
+/// modified, truncated or opened by another store while this one has it open.
+/// Opening checks the header against the link type, the internal range and the
+/// file length, and reports a mismatched or damaged one as
+/// [`Error::AllocFailed`] with [`io::ErrorKind::InvalidData`].
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{FileLinks, Flow, Links};
+///
+/// let path = std::env::temp_dir().join("platform-data-file-links-doctest.links");
+/// # let _ = std::fs::remove_file(&path);
+/// {
+///     let mut links = FileLinks::<u64>::open(&path).unwrap();
+///     links.create_links(&[], &mut |_, _| Flow::Continue).unwrap();
+/// }
+///
+/// let links = FileLinks::<u64>::open(&path).unwrap();
+/// assert_eq!(links.count_links(&[]), 1);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FileLinks<T: LinkType> {
+    constants: LinksConstants<T>,
+    file: File,
+    map: MmapMut,
+}
+
+/// View of one index over the mapped records, as required by [`SizeBalancedTree`].
+pub(crate) struct Nodes<T, R> {
+    records: R,
+    start: T,
+    by_target: bool,
+}
+
+impl<T, R> Nodes<T, R> {
+    fn new(records: R, start: T, by_target: bool) -> Self {
+        Self {
+            records,
+            start,
+            by_target,
+        }
+    }
+}
+
+impl<T: LinkType, R: AsRef<[Record<T>]>> TreeNodes<T> for Nodes<T, R> {
+    fn node(&self, link: T) -> TreeNode<T> {
+        let record = &self.records.as_ref()[(link - self.start).as_usize()];
+        if self.by_target {
+            record.targets
+        } else {
+            record.sources
+        }
+    }
+
+    fn key(&self, link: T) -> (T, T) {
+        let record = &self.records.as_ref()[(link - self.start).as_usize()];
+        if self.by_target {
+            (record.target, record.source)
+        } else {
+            (record.source, record.target)
+        }
+    }
+}
+
+impl<T: LinkType, R: AsRef<[Record<T>]> + AsMut<[Record<T>]>> TreeNodesMut<T> for Nodes<T, R> {
+    fn set_node(&mut self, link: T, node: TreeNode<T>) {
+        let record = &mut self.records.as_mut()[(link - self.start).as_usize()];
+        if self.by_target {
+            record.targets = node;
+        } else {
+            record.sources = node;
+        }
+    }
+}
+
+impl<T: LinkType> FileLinks<T> {
+    /// Opens or creates the storage at `path` with [`LinksConstants::new`].
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error<'static, T>> {
+        Self::with_constants(path, LinksConstants::new())
+    }
+
+    /// Opens or creates the storage at `path` that allocates addresses from `constants`.
+    pub fn with_constants<P: AsRef<Path>>(
+        path: P,
+        constants: LinksConstants<T>,
+    ) -> Result<Self, Error<'static, T>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let block = mem::size_of::<Record<T>>() as u64;
+        let len = file.metadata()?.len();
+        let created = len == 0;
+        if created {
+            file.set_len(block * (INITIAL_CAPACITY as u64 + 1))?;
+        } else if len % block != 0 {
+            return Err(Error::AllocFailed(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "file size is not a multiple of the link record size",
+            )));
+        }
+
+        // SAFETY: callers must not let anything else modify or truncate the
+        // file while the store is open, as the type documentation requires,
+        // and the store drops the mapping before it resizes the file
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let mut links = Self {
+            constants,
+            file,
+            map,
+        };
+        if created {
+            links.parts_mut().0.reserved = links.marker();
+        }
+        links.check_header()?;
+        Ok(links)
+    }
+
+    /// Returns the reserved words of a header written by this store.
+    fn marker(&self) -> [T; 3] {
+        [
+            T::funty(MAGIC),
+            T::funty(mem::size_of::<T>() as u8),
+            self.start(),
+        ]
+    }
+
+    /// Checks that the header was written with the same link type and
+    /// internal range, and only refers to records the file holds.
+    fn check_header(&self) -> io::Result<()> {
+        let header = self.header();
+        let [magic, size, start] = self.marker();
+        let mismatch = if header.reserved[0] != magic {
+            Some("file is not a links file")
+        } else if header.reserved[1] != size {
+            Some("file was written with a link type of a different size")
+        } else if header.reserved[2] != start {
+            Some("file was written with a different internal range")
+        } else {
+            None
+        };
+        if let Some(message) = mismatch {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        let allocated = header.allocated.as_usize();
+        let valid = |address: T| {
+            address == T::funty(0) || self.slot(address).is_some_and(|slot| slot < allocated)
+        };
+        if allocated <= self.records().len()
+            && header.count.as_usize() <= allocated
+            && valid(header.free)
+            && valid(header.source_root)
+            && valid(header.target_root)
+        {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "file header refers to records past the end of the file",
+            ))
+        }
+    }
+
+    /// Writes modified pages back to the file.
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+
+    fn parts(&self) -> (&Header<T>, &[Record<T>]) {
+        let len = self.map.len() / mem::size_of::<Record<T>>();
+        // SAFETY: the mapping is page-aligned, spans `len` blocks, and link
+        // types are plain unsigned integers that are valid for any bit pattern
+        unsafe {
+            let blocks = self.map.as_ptr() as *const Record<T>;
+            (
+                &*(blocks as *const Header<T>),
+                slice::from_raw_parts(blocks.add(1), len - 1),
+            )
+        }
+    }
+
+    fn parts_mut(&mut self) -> (&mut Header<T>, &mut [Record<T>]) {
+        let len = self.map.len() / mem::size_of::<Record<T>>();
+        // SAFETY: same as in `parts`, and the header and the records do not overlap
+        unsafe {
+            let blocks = self.map.as_mut_ptr() as *mut Record<T>;
+            (
+                &mut *(blocks as *mut Header<T>),
+                slice::from_raw_parts_mut(blocks.add(1), len - 1),
+            )
+        }
+    }
+
+    fn header(&self) -> &Header<T> {
+        self.parts().0
+    }
+
+    fn records(&self) -> &[Record<T>] {
+        self.parts().1
+    }
+
+    fn start(&self) -> T {
+        *self.constants.internal_range.start()
+    }
+
+    fn slot(&self, index: T) -> Option<usize> {
+        if self.constants.is_internal(index) {
+            Some((index - self.start()).as_usize())
+        } else {
+            None
+        }
+    }
+
+    fn address(&self, slot: usize) -> T {
+        self.start() + T::try_from(slot).unwrap()
+    }
+
+    fn exists(&self, slot: usize) -> bool {
+        slot < self.header().allocated.as_usize()
+            && self.records()[slot].sources.size != T::funty(0)
+    }
+
+    fn nodes(&self, by_target: bool) -> Nodes<T, &[Record<T>]> {
+        Nodes::new(self.records(), self.start(), by_target)
+    }
+
+    fn attach(&mut self, index: T) {
+        let start = self.start();
+        let (header, records) = self.parts_mut();
+        let mut sources = SizeBalancedTree::from_root(header.source_root);
+        sources.insert(&mut Nodes::new(&mut *records, start, false), index);
+        let mut targets = SizeBalancedTree::from_root(header.target_root);
+        targets.insert(&mut Nodes::new(records, start, true), index);
+        header.source_root = sources.root();
+        header.target_root = targets.root();
+    }
+
+    fn detach(&mut self, index: T) {
+        let start = self.start();
+        let (header, records) = self.parts_mut();
+        let mut sources = SizeBalancedTree::from_root(header.source_root);
+        sources.remove(&mut Nodes::new(&mut *records, start, false), index);
+        let mut targets = SizeBalancedTree::from_root(header.target_root);
+        targets.remove(&mut Nodes::new(records, start, true), index);
+        header.source_root = sources.root();
+        header.target_root = targets.root();
+    }
+
+    fn grow(&mut self, slots: usize) -> io::Result<()> {
+        let capacity = self.records().len();
+        if slots <= capacity {
+            return Ok(());
+        }
+
+        let capacity = slots.max(capacity * 2);
+        let block = mem::size_of::<Record<T>>() as u64;
+        self.map.flush()?;
+        self.file.set_len(block * (capacity as u64 + 1))?;
+        // SAFETY: see `with_constants`
+        self.map = unsafe { MmapMut::map_mut(&self.file)? };
+        Ok(())
+    }
+
+    fn push_free(&mut self, slot: usize) {
+        let (start, address) = (self.start(), self.address(slot));
+        let (header, records) = self.parts_mut();
+        let next = header.free;
+        records[slot].sources = TreeNode {
+            left: T::funty(0),
+            right: next,
+            size: T::funty(0),
+        };
+        if next != T::funty(0) {
+            records[(next - start).as_usize()].sources.left = address;
+        }
+        header.free = address;
+    }
+
+    fn unlink_free(&mut self, slot: usize) {
+        let start = self.start();
+        let (header, records) = self.parts_mut();
+        let TreeNode {
+            left: prev,
+            right: next,
+            ..
+        } = records[slot].sources;
+        if prev == T::funty(0) {
+            header.free = next;
+        } else {
+            records[(prev - start).as_usize()].sources.right = next;
+        }
+        if next != T::funty(0) {
+            records[(next - start).as_usize()].sources.left = prev;
+        }
+    }
+
+    fn allocate(&mut self, index: T) -> Result<usize, Error<'static, T>> {
+        let limit = *self.constants.internal_range.end();
+        let allocated = self.header().allocated.as_usize();
+        let slot = if index == self.constants.any {
+            let free = self.header().free;
+            if free != T::funty(0) {
+                let slot = (free - self.start()).as_usize();
+                self.unlink_free(slot);
+                return Ok(slot);
+            }
+            allocated
+        } else {
+            match self.slot(index) {
+                Some(slot) if self.exists(slot) => {
+                    return Err(Error::AlreadyExists(Cow::Owned(index)));
+                }
+                Some(slot) if slot < allocated => {
+                    self.unlink_free(slot);
+                    return Ok(slot);
+                }
+                Some(slot) => slot,
+                None => return Err(Error::LimitReached(limit)),
+            }
+        };
+
+        if slot > (limit - self.start()).as_usize() {
+            return Err(Error::LimitReached(limit));
+        }
+        if slot - allocated > MAX_GAP {
+            return Err(Error::AllocFailed(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "requested index is too far past the allocated links",
+            )));
+        }
+        self.grow(slot + 1)?;
+        for gap in allocated..slot {
+            self.push_free(gap);
+        }
+        self.parts_mut().0.allocated = T::try_from(slot + 1).unwrap();
+        Ok(slot)
+    }
+
+    fn query_index(&self, query: &[T]) -> T {
+        self.part(query, self.constants.index_part)
+    }
+}
+
+impl<T: LinkType> Store<T> for FileLinks<T> {
+    type Nodes<'a> = Nodes<T, &'a [Record<T>]>;
+
+    fn constants(&self) -> &LinksConstants<T> {
+        &self.constants
+    }
+
+    fn total(&self) -> T {
+        self.header().count
+    }
+
+    fn get(&self, index: T) -> Option<(T, T)> {
+        let slot = self.slot(index)?;
+        if self.exists(slot) {
+            let record = &self.records()[slot];
+            Some((record.source, record.target))
+        } else {
+            None
+        }
+    }
+
+    fn sources(&self) -> (SizeBalancedTree<T>, Self::Nodes<'_>) {
+        let tree = SizeBalancedTree::from_root(self.header().source_root);
+        (tree, self.nodes(false))
+    }
+
+    fn targets(&self) -> (SizeBalancedTree<T>, Self::Nodes<'_>) {
+        let tree = SizeBalancedTree::from_root(self.header().target_root);
+        (tree, self.nodes(true))
+    }
+
+    fn scan(&self, handler: &mut dyn FnMut(T, T, T) -> Flow) -> Flow {
+        for slot in 0..self.header().allocated.as_usize() {
+            if self.exists(slot) {
+                let record = &self.records()[slot];
+                if handler(self.address(slot), record.source, record.target).is_break() {
+                    return Flow::Break;
+                }
+            }
+        }
+        Flow::Continue
+    }
+}
+
+impl<T: LinkType> Links<T> for FileLinks<T> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.count(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let slot = self.allocate(self.query_index(query))?;
+        let (index, null) = (self.address(slot), self.constants.null);
+
+        let (header, records) = self.parts_mut();
+        records[slot].source = null;
+        records[slot].target = null;
+        header.count += T::funty(1);
+        self.attach(index);
+
+        let mut after = self.buf();
+        self.repr(&mut after, index, null, null);
+        Ok(handler(&[], &after))
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        let mut buf = self.buf();
+        Ok(self.each(query, &mut |index, source, target| {
+            self.repr(&mut buf, index, source, target);
+            handler(&buf)
+        }))
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = self.query_index(query);
+        let (old_source, old_target) = self.get(index).ok_or(Error::NotExists(index))?;
+        let source = replacement[self.constants.source_part.as_usize()];
+        let target = replacement[self.constants.target_part.as_usize()];
+
+        let slot = self.slot(index).unwrap();
+        self.detach(index);
+        let record = &mut self.parts_mut().1[slot];
+        record.source = source;
+        record.target = target;
+        self.attach(index);
+
+        let (mut before, mut after) = (self.buf(), self.buf());
+        self.repr(&mut before, index, old_source, old_target);
+        self.repr(&mut after, index, source, target);
+        Ok(handler(&before, &after))
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = self.query_index(query);
+        let (source, target) = self.get(index).ok_or(Error::NotExists(index))?;
+        self.check_usages(index)?;
+
+        let slot = self.slot(index).unwrap();
+        self.detach(index);
+        self.push_free(slot);
+        self.parts_mut().0.count -= T::funty(1);
+
+        let mut before = self.buf();
+        self.repr(&mut before, index, source, target);
+        Ok(handler(&before, &[]))
+    }
+}
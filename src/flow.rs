@@ -1,4 +1,4 @@
-use std::ops::ControlFlow;
+use core::ops::ControlFlow;
 
 /// Represents the control flow of an operation, similar to `ControlFlow`.
 ///
@@ -6,6 +6,7 @@ use std::ops::ControlFlow;
 /// to indicate whether to continue or break early.
 #[repr(usize)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Flow {
     Continue,
     Break,
@@ -62,3 +63,115 @@ impl From<Flow> for ControlFlow<()> {
         }
     }
 }
+
+/// A [`Flow`] whose `Break` carries a value, such as the link a handler was
+/// looking for.
+///
+/// It converts to and from [`ControlFlow<B>`] without losing the value, so
+/// it works with `try_for_each`, and the `*_with` methods of
+/// [`LinksExt`](crate::LinksExt) return the first break of their handler as
+/// one.
+///
+/// ```
+/// use platform_data::FlowWith;
+/// use std::ops::ControlFlow;
+///
+/// let flow: FlowWith<i32> = (0..10)
+///     .try_for_each(|i| FlowWith::from((i * i > 10).then(|| i)).into_control_flow())
+///     .into();
+/// assert_eq!(flow, FlowWith::Break(4));
+/// assert_eq!(ControlFlow::from(flow), ControlFlow::Break(4));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlowWith<B> {
+    Continue,
+    Break(B),
+}
+
+impl<B> FlowWith<B> {
+    /// Returns `true` if this is `FlowWith::Continue`.
+    pub fn is_continue(&self) -> bool {
+        matches!(self, FlowWith::Continue)
+    }
+
+    /// Returns `true` if this is `FlowWith::Break`.
+    pub fn is_break(&self) -> bool {
+        matches!(self, FlowWith::Break(_))
+    }
+
+    /// Returns the value of `FlowWith::Break`, if any.
+    pub fn break_value(self) -> Option<B> {
+        match self {
+            FlowWith::Continue => None,
+            FlowWith::Break(value) => Some(value),
+        }
+    }
+
+    /// Maps the value of `FlowWith::Break`, like [`ControlFlow::map_break`].
+    pub fn map_break<U>(self, f: impl FnOnce(B) -> U) -> FlowWith<U> {
+        match self {
+            FlowWith::Continue => FlowWith::Continue,
+            FlowWith::Break(value) => FlowWith::Break(f(value)),
+        }
+    }
+
+    /// Converts this FlowWith into a ControlFlow that can be used with try_for_each.
+    pub fn into_control_flow(self) -> ControlFlow<B> {
+        self.into()
+    }
+
+    /// Moves the value of `FlowWith::Break` into `slot`, leaving the bare
+    /// [`Flow`] that a handler returns.
+    pub(crate) fn store(self, slot: &mut Option<B>) -> Flow {
+        match self {
+            FlowWith::Continue => Flow::Continue,
+            FlowWith::Break(value) => {
+                *slot = Some(value);
+                Flow::Break
+            }
+        }
+    }
+}
+
+impl<B> From<ControlFlow<B>> for FlowWith<B> {
+    fn from(flow: ControlFlow<B>) -> Self {
+        match flow {
+            ControlFlow::Continue(()) => FlowWith::Continue,
+            ControlFlow::Break(value) => FlowWith::Break(value),
+        }
+    }
+}
+
+impl<B> From<FlowWith<B>> for ControlFlow<B> {
+    fn from(flow: FlowWith<B>) -> Self {
+        match flow {
+            FlowWith::Continue => ControlFlow::Continue(()),
+            FlowWith::Break(value) => ControlFlow::Break(value),
+        }
+    }
+}
+
+impl<B> From<Option<B>> for FlowWith<B> {
+    fn from(value: Option<B>) -> Self {
+        value.map_or(FlowWith::Continue, FlowWith::Break)
+    }
+}
+
+impl From<Flow> for FlowWith<()> {
+    fn from(flow: Flow) -> Self {
+        match flow {
+            Flow::Continue => FlowWith::Continue,
+            Flow::Break => FlowWith::Break(()),
+        }
+    }
+}
+
+impl<B> From<FlowWith<B>> for Flow {
+    fn from(flow: FlowWith<B>) -> Self {
+        match flow {
+            FlowWith::Continue => Flow::Continue,
+            FlowWith::Break(_) => Flow::Break,
+        }
+    }
+}
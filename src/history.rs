@@ -0,0 +1,277 @@
+use alloc::{collections::VecDeque, vec::Vec};
+
+use crate::{
+    transaction::record, Change, Error, Flow, LinkType, Links, LinksConstants, ReadHandler,
+    WriteHandler,
+};
+
+/// Decorator that records every mutation as an undoable step.
+///
+/// Each call to [`create_links`](Links::create_links),
+/// [`update_links`](Links::update_links) or [`delete_links`](Links::delete_links)
+/// becomes one step made of the [`Change`]s its handler receives, or joins
+/// the open group between [`begin_group`](Self::begin_group) and
+/// [`end_group`](Self::end_group). [`undo`](Self::undo) applies the inverses
+/// of the last step in reverse order, [`redo`](Self::redo) applies it again,
+/// and any new step clears what could be redone.
+///
+/// Redoing a creation and undoing a deletion create links at their old
+/// addresses, so the inner storage has to support creating links at a chosen
+/// address, as [`MemoryLinks`](crate::MemoryLinks) does.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{HistoryLinks, LinksExt, MemoryLinks};
+///
+/// let mut links = HistoryLinks::new(MemoryLinks::<u64>::new());
+/// links.begin_group();
+/// let a = links.create_link(100, 200).unwrap();
+/// let b = links.create_link(a, a).unwrap();
+/// links.end_group();
+/// links.update(b, 300, 400).unwrap();
+///
+/// links.undo().unwrap();
+/// assert_eq!(links.search(a, a), Some(b));
+/// links.undo().unwrap();
+/// assert_eq!(links.count_all(), 0);
+/// links.redo().unwrap();
+/// assert_eq!(links.search(100, 200), Some(a));
+/// ```
+#[derive(Debug)]
+pub struct HistoryLinks<T: LinkType, L> {
+    links: L,
+    constants: LinksConstants<T>,
+    undo: VecDeque<Vec<Change<T>>>,
+    redo: Vec<Vec<Change<T>>>,
+    /// Changes of the step being recorded.
+    step: Vec<Change<T>>,
+    groups: usize,
+    limit: Option<usize>,
+}
+
+impl<T: LinkType, L: Links<T>> HistoryLinks<T, L> {
+    /// Starts recording the mutations of `links`, with unlimited history.
+    pub fn new(links: L) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            step: Vec::new(),
+            groups: 0,
+            limit: None,
+        }
+    }
+
+    /// Starts recording the mutations of `links`, keeping at most `limit`
+    /// steps to undo.
+    pub fn with_limit(links: L, limit: usize) -> Self {
+        let mut history = Self::new(links);
+        history.limit = Some(limit);
+        history
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Sets the number of steps to keep, dropping the oldest ones beyond it.
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.links
+    }
+
+    /// Returns the inner storage, dropping the history.
+    pub fn into_inner(self) -> L {
+        self.links
+    }
+
+    /// Returns the number of steps that [`undo`](Self::undo) can take back.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len() + usize::from(!self.step.is_empty())
+    }
+
+    /// Returns the number of steps that [`redo`](Self::redo) can apply again.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || !self.step.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets every step, keeping the links as they are.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.step.clear();
+    }
+
+    /// Opens a group: the mutations until the matching
+    /// [`end_group`](Self::end_group) form a single step. Groups nest.
+    pub fn begin_group(&mut self) {
+        self.groups += 1;
+    }
+
+    /// Closes the innermost group, and records its step if it is the
+    /// outermost one. Does nothing without an open group.
+    pub fn end_group(&mut self) {
+        self.groups = self.groups.saturating_sub(1);
+        self.push();
+    }
+
+    /// Takes back the last step, closing any open group first.
+    ///
+    /// Returns `false` if there is nothing to undo. If the inner storage fails
+    /// to undo a change, the part of the step that was undone can be redone
+    /// and the rest can still be undone.
+    pub fn undo(&mut self) -> Result<bool, Error<'_, T>> {
+        self.groups = 0;
+        self.push();
+        let Some(mut step) = self.undo.pop_back() else {
+            return Ok(false);
+        };
+        let mut undone = Vec::new();
+        let result = Self::apply(&mut self.links, &mut step, &mut undone, Change::inverse);
+        if !undone.is_empty() {
+            self.redo.push(undone);
+        }
+        if !step.is_empty() {
+            self.undo.push_back(step);
+        }
+        result.map(|()| true)
+    }
+
+    /// Applies again the last step taken back by [`undo`](Self::undo).
+    ///
+    /// Returns `false` if there is nothing to redo. If the inner storage fails
+    /// to redo a change, the part of the step that was redone can be undone
+    /// and the rest can still be redone.
+    pub fn redo(&mut self) -> Result<bool, Error<'_, T>> {
+        let Some(mut step) = self.redo.pop() else {
+            return Ok(false);
+        };
+        let mut redone = Vec::new();
+        let result = Self::apply(&mut self.links, &mut step, &mut redone, |change| *change);
+        if !step.is_empty() {
+            self.redo.push(step);
+        }
+        if !redone.is_empty() {
+            self.undo.push_back(redone);
+            self.trim();
+        }
+        result.map(|()| true)
+    }
+
+    /// Applies `change` of the changes taken from the end of `pending`,
+    /// moving each one applied to `done`.
+    ///
+    /// Steps to undo are kept in the order they were made and steps to redo
+    /// in reverse, so either way the next change is at the end, and `done`
+    /// comes out in the order the other stack expects.
+    fn apply(
+        links: &mut L,
+        pending: &mut Vec<Change<T>>,
+        done: &mut Vec<Change<T>>,
+        change: impl Fn(&Change<T>) -> Change<T>,
+    ) -> Result<(), Error<'static, T>> {
+        while let Some(next) = pending.last() {
+            change(next)
+                .apply(links, &mut |_, _| Flow::Continue)
+                .map_err(Error::into_owned)?;
+            done.extend(pending.pop());
+        }
+        Ok(())
+    }
+
+    /// Records the current step unless a group is open.
+    fn push(&mut self) {
+        if self.groups > 0 || self.step.is_empty() {
+            return;
+        }
+        self.undo.push_back(core::mem::take(&mut self.step));
+        self.redo.clear();
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        if let Some(limit) = self.limit {
+            while self.undo.len() > limit {
+                self.undo.pop_front();
+            }
+        }
+    }
+
+    /// Ends the step of a write, and passes its result on.
+    fn write(
+        &mut self,
+        result: Result<Flow, Error<'static, T>>,
+    ) -> Result<Flow, Error<'static, T>> {
+        self.push();
+        result
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for HistoryLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.links.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let result = self
+            .links
+            .create_links(query, &mut record(&mut self.step, &self.constants, handler))
+            .map_err(Error::into_owned);
+        self.write(result)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        self.links.each_links(query, handler)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let result = self
+            .links
+            .update_links(
+                query,
+                replacement,
+                &mut record(&mut self.step, &self.constants, handler),
+            )
+            .map_err(Error::into_owned);
+        self.write(result)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let result = self
+            .links
+            .delete_links(query, &mut record(&mut self.step, &self.constants, handler))
+            .map_err(Error::into_owned);
+        self.write(result)
+    }
+}
@@ -1,10 +1,48 @@
-use crate::LinkType;
+use core::{cmp::Ordering, convert::TryFrom, fmt};
 
-#[derive(Debug, Clone, Copy, Hash, PartialOrd, PartialEq, Ord, Eq)]
+use crate::{LinkType, LinksConstants};
+
+/// An address seen as a signed number: internal addresses are positive,
+/// external references are negative, in two's complement of the width of `T`.
+///
+/// Arithmetic, ordering and formatting follow the signed value, so
+/// `Hybrid::external(5)` is `-5` and sorts before `Hybrid::internal(5)`.
+#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Hybrid<T> {
     value: T,
 }
 
+/// Classification of a [`Hybrid`] by the sign of its value.
+///
+/// For a width with `half() == T::MAX / 2`:
+///
+/// | Kind       | Raw values            | Signed values          |
+/// |------------|-----------------------|------------------------|
+/// | `Null`     | `0`                   | `0`                    |
+/// | `Internal` | `1..=half()`          | `1..=half()`           |
+/// | `External` | `half() + 1..=T::MAX` | `-(half() + 1)..=-1`   |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HybridKind {
+    Internal,
+    External,
+    Null,
+}
+
+/// Failure to build a [`Hybrid`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HybridError<T: LinkType> {
+    #[error("{0} does not fit in a hybrid of this width")]
+    OutOfRange(i128),
+
+    #[error("-{0} is outside the external range")]
+    NotExternal(T),
+}
+
 impl<T: LinkType> Hybrid<T> {
     pub fn new(value: T) -> Self {
         Self::internal(value)
@@ -24,6 +62,16 @@ impl<T: LinkType> Hybrid<T> {
         Self { value }
     }
 
+    /// Returns `-value` if it falls in the external range of `constants`.
+    pub fn try_external(value: T, constants: &LinksConstants<T>) -> Result<Self, HybridError<T>> {
+        let hybrid = Self::external(value);
+        if hybrid.is_external() && constants.is_external(hybrid.value) {
+            Ok(hybrid)
+        } else {
+            Err(HybridError::NotExternal(value))
+        }
+    }
+
     fn extend_value(value: T) -> T {
         (T::MAX - value).wrapping_add(T::funty(1))
     }
@@ -32,19 +80,172 @@ impl<T: LinkType> Hybrid<T> {
         self.value == T::funty(0)
     }
 
+    pub fn kind(&self) -> HybridKind {
+        if self.is_zero() {
+            HybridKind::Null
+        } else if self.is_negative() {
+            HybridKind::External
+        } else {
+            HybridKind::Internal
+        }
+    }
+
+    /// Returns `true` for [`HybridKind::Internal`]; zero is neither internal
+    /// nor external.
     pub fn is_internal(&self) -> bool {
-        self.value < Self::half() // || self.value == T::default()
+        self.kind() == HybridKind::Internal
     }
 
+    /// Returns `true` for [`HybridKind::External`]; zero is neither internal
+    /// nor external.
     pub fn is_external(&self) -> bool {
-        !self.is_internal() || self.value == T::funty(0)
+        self.kind() == HybridKind::External
+    }
+
+    /// Returns `true` if the signed value is greater than zero.
+    pub fn is_positive(&self) -> bool {
+        !self.is_zero() && !self.is_negative()
+    }
+
+    /// Returns `true` if the signed value is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.value > Self::half()
     }
 
+    /// Returns `1`, `0` or `-1` like [`i128::signum`].
+    pub fn signum(&self) -> i8 {
+        if self.is_negative() {
+            -1
+        } else if self.is_zero() {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Returns the magnitude of the signed value, so that
+    /// `Hybrid::external(x).abs() == x` for every `x` up to `half() + 1`.
     pub fn abs(&self) -> T {
-        self.value.wrapping_add(T::funty(1)).wrapping_add(T::MAX)
+        if self.is_negative() {
+            Self::extend_value(self.value)
+        } else {
+            self.value
+        }
     }
 
     pub const fn as_inner(&self) -> T {
         self.value
     }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let sum = i128::from(self).checked_add(i128::from(rhs))?;
+        Self::try_from(sum).ok()
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let difference = i128::from(self).checked_sub(i128::from(rhs))?;
+        Self::try_from(difference).ok()
+    }
+
+    pub fn wrapping_add(self, rhs: Self) -> Self {
+        Self {
+            value: self.value.wrapping_add(rhs.value),
+        }
+    }
+
+    pub fn wrapping_sub(self, rhs: Self) -> Self {
+        Self {
+            value: self.value.wrapping_sub(rhs.value),
+        }
+    }
+
+    fn magnitude(&self) -> u128 {
+        self.abs().try_into().expect("every link type fits in u128")
+    }
+}
+
+impl<T: LinkType> From<Hybrid<T>> for i128 {
+    fn from(hybrid: Hybrid<T>) -> Self {
+        let magnitude = hybrid.magnitude();
+        if hybrid.is_negative() {
+            // Stays in range for the magnitude of `i128::MIN`.
+            -((magnitude - 1) as i128) - 1
+        } else {
+            magnitude as i128
+        }
+    }
+}
+
+impl<T: LinkType> TryFrom<i128> for Hybrid<T> {
+    type Error = HybridError<T>;
+
+    fn try_from(value: i128) -> Result<Self, Self::Error> {
+        let half: u128 = Self::half()
+            .try_into()
+            .expect("every link type fits in u128");
+        let magnitude = value.unsigned_abs();
+        let limit = if value < 0 { half + 1 } else { half };
+        match T::try_from(magnitude) {
+            Ok(magnitude) if value.unsigned_abs() <= limit => Ok(if value < 0 {
+                Self::external(magnitude)
+            } else {
+                Self::internal(magnitude)
+            }),
+            _ => Err(HybridError::OutOfRange(value)),
+        }
+    }
 }
+
+impl<T: LinkType> PartialOrd for Hybrid<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: LinkType> Ord for Hybrid<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        i128::from(*self).cmp(&i128::from(*other))
+    }
+}
+
+/// Shows the signed value: `+5`, `-5` or `0`.
+impl<T: LinkType> fmt::Display for Hybrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.signum() {
+            1 => write!(f, "+{}", self.abs()),
+            -1 => write!(f, "-{}", self.abs()),
+            _ => write!(f, "0"),
+        }
+    }
+}
+
+impl<T: LinkType> fmt::Debug for Hybrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Hybrid")
+            .field(&format_args!("{}", self))
+            .finish()
+    }
+}
+
+macro_rules! const_hybrid {
+    ($($ty:ty),*) => {$(
+        impl Hybrid<$ty> {
+            /// Same as [`half`](Self::half), usable in `const` items and patterns.
+            pub const HALF: $ty = <$ty>::MAX / 2;
+
+            /// Same as [`internal`](Self::internal), evaluable at compile time.
+            pub const fn internal_const(value: $ty) -> Self {
+                Self { value }
+            }
+
+            /// Same as [`external`](Self::external), evaluable at compile time.
+            pub const fn external_const(value: $ty) -> Self {
+                Self {
+                    value: (<$ty>::MAX - value).wrapping_add(1),
+                }
+            }
+        }
+    )*};
+}
+
+const_hybrid!(u8, u16, u32, u64, u128, usize);
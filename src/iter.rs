@@ -0,0 +1,56 @@
+use alloc::vec::{self, Vec};
+use core::iter::FusedIterator;
+
+use crate::{Error, Flow, Link, LinkType, Links};
+
+/// Iterator over the links that matched a query, created by [`LinksExt::iter`].
+///
+/// Matches are collected when the iterator is created, so it does not borrow
+/// the storage and sees the links as they were at that moment.
+///
+/// [`LinksExt::iter`]: crate::LinksExt::iter
+#[derive(Clone, Debug)]
+pub struct LinksIter<T> {
+    links: vec::IntoIter<Link<T>>,
+}
+
+impl<T: LinkType> LinksIter<T> {
+    pub(crate) fn new<L>(links: &L, query: &[T]) -> Result<Self, Error<'static, T>>
+    where
+        L: Links<T> + ?Sized,
+    {
+        let constants = links.constants_links();
+        let mut found = Vec::with_capacity(links.count_links(query).as_usize());
+        links
+            .each_links(query, &mut |link| {
+                found.push(Link::from_slice(link, &constants));
+                Flow::Continue
+            })
+            .map_err(Error::into_owned)?;
+        Ok(Self {
+            links: found.into_iter(),
+        })
+    }
+}
+
+impl<T> Iterator for LinksIter<T> {
+    type Item = Link<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.links.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.links.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for LinksIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.links.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for LinksIter<T> {}
+
+impl<T> FusedIterator for LinksIter<T> {}
@@ -1,17 +1,79 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod async_links;
+#[cfg(feature = "tokio")]
+mod blocking;
+mod change;
 mod constants;
+mod context;
 mod converters;
+mod ext;
+#[cfg(feature = "std")]
+mod file;
 mod flow;
+mod history;
 mod hybrid;
+mod iter;
+mod link;
 mod link_type;
 mod links;
+#[cfg(feature = "std")]
+mod lino;
+mod mem;
+#[cfg(feature = "std")]
+mod mvcc;
 mod point;
 mod query;
+#[cfg(feature = "std")]
+mod shared;
+mod store;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod transaction;
+mod tree;
+mod unique;
+mod usage;
+mod validation;
+#[cfg(feature = "std")]
+mod wal;
 
-pub use constants::LinksConstants;
+pub use async_links::AsyncLinks;
+#[cfg(feature = "tokio")]
+pub use blocking::{BlockingLinks, SpawnedLinks};
+pub use change::Change;
+pub use constants::{ConstantsError, LinksConstants, LinksConstantsBuilder};
+pub use context::{LinksError, Operation};
 pub use converters::{AddrToRaw, RawToAddr};
-pub use flow::Flow;
-pub use hybrid::Hybrid;
+pub use ext::LinksExt;
+#[cfg(feature = "std")]
+pub use file::FileLinks;
+pub use flow::{Flow, FlowWith};
+pub use history::HistoryLinks;
+pub use hybrid::{Hybrid, HybridError, HybridKind};
+pub use iter::LinksIter;
+pub use link::{read_handler, write_handler, Link};
 pub use link_type::LinkType;
-pub use links::{Error, Links, ReadHandler, WriteHandler};
+pub use links::{Error, ErrorKind, Links, ReadHandler, WriteHandler};
+#[cfg(feature = "std")]
+pub use lino::{read_lino, to_lino, write_lino, LinoError, LinoPosition};
+pub use mem::MemoryLinks;
+#[cfg(feature = "std")]
+pub use mvcc::{Snapshot, VersionedLinks};
 pub use point::{Point, PointIter};
 pub use query::{Query, ToQuery};
+#[cfg(feature = "std")]
+pub use shared::SharedLinks;
+pub use transaction::{Savepoint, TransactionError, TransactionLinks};
+pub use tree::{SizeBalancedTree, TreeNode, TreeNodes, TreeNodesMut};
+pub use unique::{DuplicatePolicy, UniqueLinks};
+pub use usage::{DeleteMode, UsageLinks};
+pub use validation::{ValidatedLinks, ValidationError};
+#[cfg(feature = "std")]
+pub use wal::{Durable, Recovery, SyncPolicy, WalError, WalLinks};
+
+#[cfg(not(feature = "std"))]
+use core::error;
+#[cfg(feature = "std")]
+use std::error;
@@ -0,0 +1,156 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{Flow, LinkType, LinksConstants};
+
+/// A single doublet link: its address and the addresses it connects.
+///
+/// Storages exchange links as slices whose layout is given by
+/// [`LinksConstants::index_part`], [`source_part`](LinksConstants::source_part)
+/// and [`target_part`](LinksConstants::target_part). `Link` names those parts,
+/// so handlers can destructure them instead of indexing:
+///
+/// ```
+/// use platform_data::{read_handler, Flow, Link, Links, LinksExt, MemoryLinks};
+///
+/// let mut links = MemoryLinks::<usize>::new();
+/// let point = links.create_link(1, 1).unwrap();
+///
+/// let constants = links.constants_links();
+/// let mut sources = vec![];
+/// links
+///     .each_links(
+///         &[],
+///         &mut read_handler(&constants, |Link { source, .. }| {
+///             sources.push(source);
+///             Flow::Continue
+///         }),
+///     )
+///     .unwrap();
+/// assert_eq!(sources, [point]);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Link<T> {
+    pub index: T,
+    pub source: T,
+    pub target: T,
+}
+
+impl<T> Link<T> {
+    pub const fn new(index: T, source: T, target: T) -> Self {
+        Self {
+            index,
+            source,
+            target,
+        }
+    }
+}
+
+impl<T: LinkType> Link<T> {
+    /// Returns the link with every part set to zero, which stands for the
+    /// empty slice a [`WriteHandler`](crate::WriteHandler) receives before a
+    /// creation or after a deletion.
+    pub fn nothing() -> Self {
+        Self::point(T::funty(0))
+    }
+
+    /// Returns the link whose parts are all `index`.
+    pub fn point(index: T) -> Self {
+        Self::new(index, index, index)
+    }
+
+    pub fn is_nothing(&self) -> bool {
+        *self == Self::nothing()
+    }
+
+    /// Returns `true` if both source and target are the link itself.
+    pub fn is_full(&self) -> bool {
+        self.index == self.source && self.index == self.target
+    }
+
+    /// Returns `true` if source or target is the link itself.
+    pub fn is_partial(&self) -> bool {
+        self.index == self.source || self.index == self.target
+    }
+
+    /// Reads a link from a slice laid out by `constants`.
+    ///
+    /// An empty slice gives [`Link::nothing`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a non-empty slice is too short to hold the target part.
+    pub fn from_slice(link: &[T], constants: &LinksConstants<T>) -> Self {
+        if link.is_empty() {
+            return Self::nothing();
+        }
+        Self::new(
+            link[constants.index_part.as_usize()],
+            link[constants.source_part.as_usize()],
+            link[constants.target_part.as_usize()],
+        )
+    }
+
+    /// Writes the link into a slice laid out by `constants`, leaving other parts untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is too short to hold the target part.
+    pub fn write_to(&self, link: &mut [T], constants: &LinksConstants<T>) {
+        link[constants.index_part.as_usize()] = self.index;
+        link[constants.source_part.as_usize()] = self.source;
+        link[constants.target_part.as_usize()] = self.target;
+    }
+
+    /// Returns the link as a slice laid out by `constants`, with unused parts set to `null`.
+    pub fn to_vec(&self, constants: &LinksConstants<T>) -> Vec<T> {
+        let mut link = vec![constants.null; constants.target_part.as_usize() + 1];
+        self.write_to(&mut link, constants);
+        link
+    }
+}
+
+/// Uses the default `[index, source, target]` layout.
+impl<T> From<[T; 3]> for Link<T> {
+    fn from([index, source, target]: [T; 3]) -> Self {
+        Self::new(index, source, target)
+    }
+}
+
+/// Uses the default `[index, source, target]` layout.
+impl<T> From<Link<T>> for [T; 3] {
+    fn from(link: Link<T>) -> Self {
+        [link.index, link.source, link.target]
+    }
+}
+
+/// Adapts a handler of [`Link`]s to a [`ReadHandler`](crate::ReadHandler).
+pub fn read_handler<T, F>(constants: &LinksConstants<T>, mut handler: F) -> impl FnMut(&[T]) -> Flow
+where
+    T: LinkType,
+    F: FnMut(Link<T>) -> Flow,
+{
+    let constants = constants.clone();
+    move |link| handler(Link::from_slice(link, &constants))
+}
+
+/// Adapts a handler of `(before, after)` [`Link`]s to a [`WriteHandler`](crate::WriteHandler).
+///
+/// Empty slices, as reported before a creation or after a deletion, are
+/// passed as [`Link::nothing`].
+pub fn write_handler<T, F>(
+    constants: &LinksConstants<T>,
+    mut handler: F,
+) -> impl FnMut(&[T], &[T]) -> Flow
+where
+    T: LinkType,
+    F: FnMut(Link<T>, Link<T>) -> Flow,
+{
+    let constants = constants.clone();
+    move |before, after| {
+        handler(
+            Link::from_slice(before, &constants),
+            Link::from_slice(after, &constants),
+        )
+    }
+}
@@ -1,8 +1,8 @@
-use funty::Unsigned;
-use std::{
+use core::{
     convert::{TryFrom, TryInto},
     fmt::Debug,
 };
+use funty::Unsigned;
 
 /// Trait for creating small numeric values from u8.
 ///
@@ -1,5 +1,13 @@
-use crate::{Flow, LinkType, LinksConstants};
-use std::{borrow::Cow, error, io};
+#[cfg(feature = "std")]
+use crate::WalError;
+use crate::{
+    error, Flow, LinkType, LinksConstants, LinksError, Operation, TransactionError, ValidationError,
+};
+#[cfg(not(feature = "std"))]
+use alloc::collections::TryReserveError;
+use alloc::{borrow::Cow, boxed::Box, vec::Vec};
+#[cfg(feature = "std")]
+use std::io;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error<'a, T: LinkType> {
@@ -15,11 +23,96 @@ pub enum Error<'a, T: LinkType> {
     #[error("limit for the number of links in the storage has been reached: {0}")]
     LimitReached(T),
 
+    #[cfg(feature = "std")]
     #[error("unable to allocate memory for links storage: `{0}`")]
     AllocFailed(#[from] io::Error),
 
+    /// Without `std`, storages can only fail to allocate by running out of memory.
+    #[cfg(not(feature = "std"))]
+    #[error("unable to allocate memory for links storage: `{0}`")]
+    AllocFailed(#[from] TryReserveError),
+
     #[error("other internal error: `{0}`")]
     Other(#[from] Box<dyn error::Error + Sync + Send>),
+
+    #[error("transaction failed: {0}")]
+    Transaction(Box<TransactionError<T>>),
+
+    #[cfg(feature = "std")]
+    #[error("write-ahead log failed: {0}")]
+    Wal(#[from] WalError),
+
+    #[error("invalid link: {0}")]
+    Invalid(#[from] ValidationError<T>),
+
+    #[error("storage is read-only")]
+    ReadOnly,
+}
+
+/// Machine-readable kind of an [`Error`], one per variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    NotExists,
+    HasUsages,
+    AlreadyExists,
+    LimitReached,
+    AllocFailed,
+    Other,
+    Transaction,
+    Wal,
+    Invalid,
+    ReadOnly,
+}
+
+impl<T: LinkType> From<TransactionError<T>> for Error<'_, T> {
+    fn from(error: TransactionError<T>) -> Self {
+        Error::Transaction(Box::new(error))
+    }
+}
+
+impl<T: LinkType> Error<'_, T> {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::NotExists(_) => ErrorKind::NotExists,
+            Error::HasUsages(_) => ErrorKind::HasUsages,
+            Error::AlreadyExists(_) => ErrorKind::AlreadyExists,
+            Error::LimitReached(_) => ErrorKind::LimitReached,
+            Error::AllocFailed(_) => ErrorKind::AllocFailed,
+            Error::Other(_) => ErrorKind::Other,
+            Error::Transaction(_) => ErrorKind::Transaction,
+            #[cfg(feature = "std")]
+            Error::Wal(_) => ErrorKind::Wal,
+            Error::Invalid(_) => ErrorKind::Invalid,
+            Error::ReadOnly => ErrorKind::ReadOnly,
+        }
+    }
+
+    /// Makes the error owned and records what failed, see [`LinksError`].
+    pub fn context(self, operation: Operation, query: &[T]) -> LinksError<T> {
+        LinksError::from(self).with_operation(operation, query)
+    }
+
+    /// Copies any borrowed data so the error no longer borrows the storage.
+    pub fn into_owned(self) -> Error<'static, T> {
+        match self {
+            Error::NotExists(index) => Error::NotExists(index),
+            Error::HasUsages(usages) => Error::HasUsages(
+                usages
+                    .into_iter()
+                    .map(|usage| Cow::Owned(usage.into_owned()))
+                    .collect(),
+            ),
+            Error::AlreadyExists(index) => Error::AlreadyExists(Cow::Owned(index.into_owned())),
+            Error::LimitReached(limit) => Error::LimitReached(limit),
+            Error::AllocFailed(error) => Error::AllocFailed(error),
+            Error::Other(error) => Error::Other(error),
+            Error::Transaction(error) => Error::Transaction(error),
+            #[cfg(feature = "std")]
+            Error::Wal(error) => Error::Wal(error),
+            Error::Invalid(error) => Error::Invalid(error),
+            Error::ReadOnly => Error::ReadOnly,
+        }
+    }
 }
 
 pub type ReadHandler<'a, T> = &'a mut dyn FnMut(&[T]) -> Flow;
@@ -0,0 +1,447 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+    iter::Peekable,
+    marker::PhantomData,
+    str::Chars,
+};
+
+use crate::{Error, Flow, Link, LinkType, Links, LinksExt};
+
+/// Deepest nesting of links that [`read_lino`] accepts, which bounds the
+/// recursion over nested links.
+const MAX_DEPTH: usize = 256;
+
+/// Line and column of a character in LiNo text, both counted from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LinoPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for LinoPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Error returned by [`read_lino`], with the position of the offending text.
+#[derive(thiserror::Error, Debug)]
+pub enum LinoError<T: LinkType> {
+    #[error("{position}: expected {expected}, found {found}")]
+    Unexpected {
+        position: LinoPosition,
+        expected: &'static str,
+        found: String,
+    },
+
+    #[error("{position}: a doublet has two references, found {found}")]
+    Arity {
+        position: LinoPosition,
+        found: usize,
+    },
+
+    #[error("{position}: `{number}` does not fit the link type")]
+    Overflow {
+        position: LinoPosition,
+        number: String,
+    },
+
+    #[error("{position}: `{name}` is used but never defined")]
+    Undefined {
+        position: LinoPosition,
+        name: String,
+    },
+
+    #[error("{position}: link {index} is used but neither exists nor is defined")]
+    Missing { position: LinoPosition, index: T },
+
+    #[error("{position}: links are nested more than {limit} deep")]
+    TooDeep {
+        position: LinoPosition,
+        limit: usize,
+    },
+
+    #[error("{position}: {error}")]
+    Links {
+        position: LinoPosition,
+        error: Error<'static, T>,
+    },
+}
+
+/// Reads links written in Links Notation into `links`, returning the address
+/// of every name the text defines.
+///
+/// Each link is written as `(index: source target)`, and the index is optional.
+/// References are numbers, names or nested links:
+///
+/// - a number is a link address, and a numbered index creates the link at that
+///   address unless it exists already; an internal address used as a
+///   reference must exist or be defined in the text;
+/// - a name stands for the link defined as `(name: source target)`, which may
+///   come later in the text or refer to itself;
+/// - a link without an index, such as the nested `(1 2)`, is the existing link
+///   with that source and target, or a new one if there is none.
+///
+/// Existing links named by an index are updated. The whole text is parsed and
+/// its references are checked before `links` is touched, so syntax errors and
+/// missing links leave the storage unchanged. An error from the storage itself
+/// stops the import part-way, and the links read until then stay in place.
+/// Links may be nested at most 256 deep.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{read_lino, Link, LinksExt, MemoryLinks};
+///
+/// let mut links = MemoryLinks::<usize>::new();
+/// let names = read_lino(&mut links, "(papa: papa mama) (mama: mama papa) (son: (papa mama) son)")
+///     .unwrap();
+///
+/// let (papa, mama, son) = (names["papa"], names["mama"], names["son"]);
+/// assert_eq!(links.get_link(papa), Some(Link::new(papa, papa, mama)));
+/// let parents = links.search(papa, mama).unwrap();
+/// assert_eq!(links.get_link(son), Some(Link::new(son, parents, son)));
+/// ```
+pub fn read_lino<T: LinkType, L: Links<T> + ?Sized>(
+    links: &mut L,
+    text: &str,
+) -> Result<HashMap<String, T>, LinoError<T>> {
+    let document = Parser::new(text).document()?;
+
+    let constants = links.constants_links();
+    let (mut defined, mut numbers) = (HashSet::new(), BTreeSet::new());
+    for node in &document {
+        node.visit(&mut |node| match &node.index {
+            Some(Word::Name(name, _)) => {
+                defined.insert(name.as_str());
+            }
+            Some(Word::Number(index, _)) => {
+                numbers.insert(*index);
+            }
+            None => {}
+        });
+    }
+    let mut undefined = None;
+    for node in &document {
+        node.visit(&mut |node| {
+            for reference in &node.references {
+                let error = match reference {
+                    Reference::Word(Word::Name(name, position))
+                        if !defined.contains(name.as_str()) =>
+                    {
+                        LinoError::Undefined {
+                            position: *position,
+                            name: name.clone(),
+                        }
+                    }
+                    Reference::Word(Word::Number(index, position))
+                        if constants.is_internal(*index)
+                            && !numbers.contains(index)
+                            && !links.exists(*index) =>
+                    {
+                        LinoError::Missing {
+                            position: *position,
+                            index: *index,
+                        }
+                    }
+                    _ => continue,
+                };
+                undefined.get_or_insert(error);
+            }
+        });
+    }
+    if let Some(error) = undefined {
+        return Err(error);
+    }
+
+    let mut reader = Reader {
+        links,
+        names: HashMap::new(),
+    };
+    for node in &document {
+        reader.link(node)?;
+    }
+    Ok(reader.names)
+}
+
+/// Writes every link of `links` as `(index: source target)`, one per line in
+/// the order of [`Links::each_links`].
+///
+/// Reading the output with [`read_lino`] into an empty storage recreates the
+/// links at the same addresses.
+pub fn write_lino<'a, T: LinkType, L: Links<T> + ?Sized>(
+    links: &'a L,
+    out: &mut impl fmt::Write,
+) -> Result<(), Error<'a, T>> {
+    let constants = links.constants_links();
+    let mut result = Ok(());
+    links.each_links(&[], &mut |link| {
+        let Link {
+            index,
+            source,
+            target,
+        } = Link::from_slice(link, &constants);
+        result = writeln!(out, "({}: {} {})", index, source, target);
+        if result.is_ok() {
+            Flow::Continue
+        } else {
+            Flow::Break
+        }
+    })?;
+    result.map_err(|error| Error::Other(Box::new(error)))
+}
+
+/// Returns every link of `links` in Links Notation, as written by [`write_lino`].
+pub fn to_lino<T: LinkType, L: Links<T> + ?Sized>(links: &L) -> Result<String, Error<'_, T>> {
+    let mut text = String::new();
+    write_lino(links, &mut text)?;
+    Ok(text)
+}
+
+enum Word<T> {
+    Number(T, LinoPosition),
+    Name(String, LinoPosition),
+}
+
+enum Reference<T> {
+    Word(Word<T>),
+    Link(Node<T>),
+}
+
+struct Node<T> {
+    index: Option<Word<T>>,
+    references: Vec<Reference<T>>,
+    position: LinoPosition,
+}
+
+impl<T> Node<T> {
+    fn visit<'a>(&'a self, visitor: &mut dyn FnMut(&'a Node<T>)) {
+        visitor(self);
+        for reference in &self.references {
+            if let Reference::Link(node) = reference {
+                node.visit(visitor);
+            }
+        }
+    }
+}
+
+struct Parser<'a, T> {
+    chars: Peekable<Chars<'a>>,
+    position: LinoPosition,
+    depth: usize,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T: LinkType> Parser<'a, T> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            position: LinoPosition { line: 1, column: 1 },
+            depth: 0,
+            marker: PhantomData,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let char = self.chars.next()?;
+        if char == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(char)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn unexpected(&mut self, expected: &'static str) -> LinoError<T> {
+        let found = match self.peek() {
+            Some(char) => format!("`{}`", char),
+            None => "end of input".to_owned(),
+        };
+        LinoError::Unexpected {
+            position: self.position,
+            expected,
+            found,
+        }
+    }
+
+    fn document(mut self) -> Result<Vec<Node<T>>, LinoError<T>> {
+        let mut document = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(document),
+                Some('(') => document.push(self.link()?),
+                Some(_) => return Err(self.unexpected("`(`")),
+            }
+        }
+    }
+
+    fn link(&mut self) -> Result<Node<T>, LinoError<T>> {
+        let position = self.position;
+        if self.depth == MAX_DEPTH {
+            return Err(LinoError::TooDeep {
+                position,
+                limit: MAX_DEPTH,
+            });
+        }
+        self.depth += 1;
+        let node = self.nested(position);
+        self.depth -= 1;
+        node
+    }
+
+    /// Reads the rest of the link opened at `position`.
+    fn nested(&mut self, position: LinoPosition) -> Result<Node<T>, LinoError<T>> {
+        self.bump();
+        let mut index = None;
+        let mut references = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(')') => {
+                    self.bump();
+                    break;
+                }
+                Some('(') => references.push(Reference::Link(self.link()?)),
+                Some(':') if index.is_none() && matches!(references[..], [Reference::Word(_)]) => {
+                    self.bump();
+                    if let Some(Reference::Word(word)) = references.pop() {
+                        index = Some(word);
+                    }
+                }
+                Some(char) if is_word(char) => references.push(Reference::Word(self.word()?)),
+                _ => return Err(self.unexpected("a reference or `)`")),
+            }
+        }
+        if references.len() != 2 {
+            return Err(LinoError::Arity {
+                position,
+                found: references.len(),
+            });
+        }
+        Ok(Node {
+            index,
+            references,
+            position,
+        })
+    }
+
+    fn word(&mut self) -> Result<Word<T>, LinoError<T>> {
+        let position = self.position;
+        let mut word = String::new();
+        match self.peek() {
+            Some(quote @ ('\'' | '"')) => {
+                self.bump();
+                loop {
+                    match self.bump() {
+                        Some(char) if char == quote => break,
+                        Some(char) => word.push(char),
+                        None => return Err(self.unexpected("a closing quote")),
+                    }
+                }
+                return Ok(Word::Name(word, position));
+            }
+            _ => {
+                while let Some(char) = self.peek().filter(|&char| is_word(char)) {
+                    word.push(char);
+                    self.bump();
+                }
+            }
+        }
+
+        if !word.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Ok(Word::Name(word, position));
+        }
+        match word.parse::<u128>().ok().and_then(|n| T::try_from(n).ok()) {
+            Some(number) => Ok(Word::Number(number, position)),
+            None => Err(LinoError::Overflow {
+                position,
+                number: word,
+            }),
+        }
+    }
+}
+
+fn is_word(char: char) -> bool {
+    !char.is_whitespace() && !matches!(char, '(' | ')' | ':')
+}
+
+struct Reader<'a, T, L: ?Sized> {
+    links: &'a mut L,
+    names: HashMap<String, T>,
+}
+
+impl<T: LinkType, L: Links<T> + ?Sized> Reader<'_, T, L> {
+    /// Returns the address of a link index, creating the link if needed.
+    fn define(&mut self, word: &Word<T>) -> Result<T, LinoError<T>> {
+        match word {
+            Word::Number(index, position) => {
+                if !self.links.exists(*index) {
+                    self.links
+                        .create_links(&[*index], &mut |_, _| Flow::Continue)
+                        .map_err(|error| failed(*position, error))?;
+                }
+                Ok(*index)
+            }
+            Word::Name(name, position) => self.name(name, *position),
+        }
+    }
+
+    fn name(&mut self, name: &str, position: LinoPosition) -> Result<T, LinoError<T>> {
+        if let Some(&index) = self.names.get(name) {
+            return Ok(index);
+        }
+        let index = self
+            .links
+            .create()
+            .map_err(|error| failed(position, error))?;
+        self.names.insert(name.to_owned(), index);
+        Ok(index)
+    }
+
+    fn reference(&mut self, reference: &Reference<T>) -> Result<T, LinoError<T>> {
+        match reference {
+            Reference::Word(Word::Number(index, _)) => Ok(*index),
+            Reference::Word(Word::Name(name, position)) => self.name(name, *position),
+            Reference::Link(node) => self.link(node),
+        }
+    }
+
+    fn link(&mut self, node: &Node<T>) -> Result<T, LinoError<T>> {
+        let index = match &node.index {
+            Some(word) => Some(self.define(word)?),
+            None => None,
+        };
+        let source = self.reference(&node.references[0])?;
+        let target = self.reference(&node.references[1])?;
+        let position = node.position;
+
+        match index {
+            Some(index) => self.links.update(index, source, target),
+            None => match self.links.search(source, target) {
+                Some(index) => Ok(index),
+                None => self.links.create_link(source, target),
+            },
+        }
+        .map_err(|error| failed(position, error))
+    }
+}
+
+fn failed<T: LinkType>(position: LinoPosition, error: Error<'_, T>) -> LinoError<T> {
+    LinoError::Links {
+        position,
+        error: error.into_owned(),
+    }
+}
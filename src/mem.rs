@@ -0,0 +1,341 @@
+use alloc::{
+    borrow::Cow,
+    collections::{BTreeSet, TryReserveError},
+    vec::Vec,
+};
+
+use crate::{
+    store::Store, Error, Flow, LinkType, Links, LinksConstants, ReadHandler, SizeBalancedTree,
+    TreeNode, TreeNodes, TreeNodesMut, WriteHandler,
+};
+
+/// Reference implementation of [`Links`] that keeps every link in a vector.
+///
+/// Links are indexed by source and by target with [`SizeBalancedTree`]s, so
+/// queries that restrict the source, the target or both take logarithmic time
+/// plus the number of matching links.
+///
+/// Query layout follows [`LinksConstants`]: a query of length `0` matches every
+/// link, `[index]` matches a single link, `[index, value]` matches links that use
+/// `value` as source or target, and a full query restricts index, source and
+/// target at their part positions. Any position may hold `constants.any`.
+///
+/// `create_links` allocates the lowest free address, or exactly `query[index_part]`
+/// when the query names one. Addresses are taken from
+/// [`LinksConstants::internal_range`], and [`Error::LimitReached`] is returned once
+/// it is used up.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{Flow, Links, MemoryLinks};
+///
+/// let mut links = MemoryLinks::<usize>::new();
+/// let mut index = 0;
+/// links
+///     .create_links(&[], &mut |_, after| {
+///         index = after[0];
+///         Flow::Continue
+///     })
+///     .unwrap();
+///
+/// links
+///     .update_links(&[index], &[index, index, index], &mut |_, _| Flow::Continue)
+///     .unwrap();
+/// assert_eq!(links.count_links(&[links.constants_links().any, index, index]), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MemoryLinks<T: LinkType> {
+    constants: LinksConstants<T>,
+    links: Vec<Option<(T, T)>>,
+    sources: Vec<TreeNode<T>>,
+    targets: Vec<TreeNode<T>>,
+    source_tree: SizeBalancedTree<T>,
+    target_tree: SizeBalancedTree<T>,
+    free: BTreeSet<usize>,
+    count: usize,
+}
+
+/// View of one index over the link table, as required by [`SizeBalancedTree`].
+pub(crate) struct Index<'a, T: LinkType, N> {
+    links: &'a [Option<(T, T)>],
+    nodes: N,
+    start: T,
+    by_target: bool,
+}
+
+impl<T: LinkType, N: AsRef<[TreeNode<T>]>> TreeNodes<T> for Index<'_, T, N> {
+    fn node(&self, link: T) -> TreeNode<T> {
+        self.nodes.as_ref()[(link - self.start).as_usize()]
+    }
+
+    fn key(&self, link: T) -> (T, T) {
+        let (source, target) = self.links[(link - self.start).as_usize()].unwrap();
+        if self.by_target {
+            (target, source)
+        } else {
+            (source, target)
+        }
+    }
+}
+
+impl<T: LinkType, N: AsRef<[TreeNode<T>]> + AsMut<[TreeNode<T>]>> TreeNodesMut<T>
+    for Index<'_, T, N>
+{
+    fn set_node(&mut self, link: T, node: TreeNode<T>) {
+        self.nodes.as_mut()[(link - self.start).as_usize()] = node;
+    }
+}
+
+type IndexMut<'a, T> = Index<'a, T, &'a mut [TreeNode<T>]>;
+
+fn indexes<'a, T: LinkType>(
+    links: &'a [Option<(T, T)>],
+    sources: &'a mut [TreeNode<T>],
+    targets: &'a mut [TreeNode<T>],
+    start: T,
+) -> (IndexMut<'a, T>, IndexMut<'a, T>) {
+    let index = |nodes, by_target| Index {
+        links,
+        nodes,
+        start,
+        by_target,
+    };
+    (index(sources, false), index(targets, true))
+}
+
+impl<T: LinkType> MemoryLinks<T> {
+    /// Creates an empty storage with [`LinksConstants::new`].
+    pub fn new() -> Self {
+        Self::with_constants(LinksConstants::new())
+    }
+
+    /// Creates an empty storage that allocates addresses from `constants`.
+    pub fn with_constants(constants: LinksConstants<T>) -> Self {
+        Self {
+            constants,
+            links: Vec::new(),
+            sources: Vec::new(),
+            targets: Vec::new(),
+            source_tree: SizeBalancedTree::new(),
+            target_tree: SizeBalancedTree::new(),
+            free: BTreeSet::new(),
+            count: 0,
+        }
+    }
+
+    fn slot(&self, index: T) -> Option<usize> {
+        if self.constants.is_internal(index) {
+            Some((index - *self.constants.internal_range.start()).as_usize())
+        } else {
+            None
+        }
+    }
+
+    fn address(&self, slot: usize) -> T {
+        *self.constants.internal_range.start() + T::try_from(slot).unwrap()
+    }
+
+    fn index<'a>(
+        &'a self,
+        nodes: &'a [TreeNode<T>],
+        by_target: bool,
+    ) -> Index<'a, T, &'a [TreeNode<T>]> {
+        Index {
+            links: &self.links,
+            nodes,
+            start: *self.constants.internal_range.start(),
+            by_target,
+        }
+    }
+
+    fn attach(&mut self, index: T) {
+        let start = *self.constants.internal_range.start();
+        let (mut sources, mut targets) =
+            indexes(&self.links, &mut self.sources, &mut self.targets, start);
+        self.source_tree.insert(&mut sources, index);
+        self.target_tree.insert(&mut targets, index);
+    }
+
+    fn detach(&mut self, index: T) {
+        let start = *self.constants.internal_range.start();
+        let (mut sources, mut targets) =
+            indexes(&self.links, &mut self.sources, &mut self.targets, start);
+        self.source_tree.remove(&mut sources, index);
+        self.target_tree.remove(&mut targets, index);
+    }
+
+    fn query_index(&self, query: &[T]) -> T {
+        self.part(query, self.constants.index_part)
+    }
+
+    fn allocate(&mut self, index: T) -> Result<usize, Error<'static, T>> {
+        let limit = *self.constants.internal_range.end();
+        let slot = if index == self.constants.any {
+            let slot = self.free.iter().next().copied().unwrap_or(self.links.len());
+            if slot > (limit - *self.constants.internal_range.start()).as_usize() {
+                return Err(Error::LimitReached(limit));
+            }
+            slot
+        } else {
+            match self.slot(index) {
+                Some(slot) if self.links.get(slot).copied().flatten().is_some() => {
+                    return Err(Error::AlreadyExists(Cow::Owned(index)));
+                }
+                Some(slot) => slot,
+                None => return Err(Error::LimitReached(limit)),
+            }
+        };
+        if slot >= self.links.len() {
+            self.grow(slot)?;
+        }
+        self.free.remove(&slot);
+        Ok(slot)
+    }
+
+    /// Extends the vectors to hold `slot`, reporting a failed allocation
+    /// instead of aborting.
+    fn grow(&mut self, slot: usize) -> Result<(), Error<'static, T>> {
+        let additional = slot + 1 - self.links.len();
+        self.links
+            .try_reserve(additional)
+            .and_then(|()| self.sources.try_reserve(additional))
+            .and_then(|()| self.targets.try_reserve(additional))
+            .map_err(alloc_failed)?;
+        self.free.extend(self.links.len()..slot);
+        self.links.resize(slot + 1, None);
+        self.sources.resize(slot + 1, TreeNode::default());
+        self.targets.resize(slot + 1, TreeNode::default());
+        Ok(())
+    }
+}
+
+impl<T: LinkType> Store<T> for MemoryLinks<T> {
+    type Nodes<'a> = Index<'a, T, &'a [TreeNode<T>]>;
+
+    fn constants(&self) -> &LinksConstants<T> {
+        &self.constants
+    }
+
+    fn total(&self) -> T {
+        T::try_from(self.count).unwrap()
+    }
+
+    fn get(&self, index: T) -> Option<(T, T)> {
+        self.slot(index)
+            .and_then(|slot| self.links.get(slot).copied().flatten())
+    }
+
+    fn sources(&self) -> (SizeBalancedTree<T>, Self::Nodes<'_>) {
+        (self.source_tree, self.index(&self.sources, false))
+    }
+
+    fn targets(&self) -> (SizeBalancedTree<T>, Self::Nodes<'_>) {
+        (self.target_tree, self.index(&self.targets, true))
+    }
+
+    fn scan(&self, handler: &mut dyn FnMut(T, T, T) -> Flow) -> Flow {
+        for (slot, link) in self.links.iter().enumerate() {
+            if let Some((source, target)) = *link {
+                if handler(self.address(slot), source, target).is_break() {
+                    return Flow::Break;
+                }
+            }
+        }
+        Flow::Continue
+    }
+}
+
+impl<T: LinkType> Default for MemoryLinks<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: LinkType> Links<T> for MemoryLinks<T> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.count(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let slot = self.allocate(self.query_index(query))?;
+
+        let null = self.constants.null;
+        self.links[slot] = Some((null, null));
+        self.attach(self.address(slot));
+        self.count += 1;
+
+        let mut after = self.buf();
+        self.repr(&mut after, self.address(slot), null, null);
+        Ok(handler(&[], &after))
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        let mut buf = self.buf();
+        Ok(self.each(query, &mut |index, source, target| {
+            self.repr(&mut buf, index, source, target);
+            handler(&buf)
+        }))
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = self.query_index(query);
+        let (old_source, old_target) = self.get(index).ok_or(Error::NotExists(index))?;
+        let source = replacement[self.constants.source_part.as_usize()];
+        let target = replacement[self.constants.target_part.as_usize()];
+
+        let slot = self.slot(index).unwrap();
+        self.detach(index);
+        self.links[slot] = Some((source, target));
+        self.attach(index);
+
+        let (mut before, mut after) = (self.buf(), self.buf());
+        self.repr(&mut before, index, old_source, old_target);
+        self.repr(&mut after, index, source, target);
+        Ok(handler(&before, &after))
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = self.query_index(query);
+        let (source, target) = self.get(index).ok_or(Error::NotExists(index))?;
+
+        self.check_usages(index)?;
+
+        let slot = self.slot(index).unwrap();
+        self.detach(index);
+        self.links[slot] = None;
+        self.free.insert(slot);
+        self.count -= 1;
+
+        let mut before = self.buf();
+        self.repr(&mut before, index, source, target);
+        Ok(handler(&before, &[]))
+    }
+}
+
+#[cfg(feature = "std")]
+fn alloc_failed<T: LinkType>(error: TryReserveError) -> Error<'static, T> {
+    Error::AllocFailed(std::io::Error::new(std::io::ErrorKind::OutOfMemory, error))
+}
+
+#[cfg(not(feature = "std"))]
+fn alloc_failed<T: LinkType>(error: TryReserveError) -> Error<'static, T> {
+    Error::AllocFailed(error)
+}
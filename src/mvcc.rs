@@ -0,0 +1,417 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    iter,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{
+    shared::replay, store::matches, Error, Flow, LinkType, Links, LinksConstants, ReadHandler,
+    WriteHandler,
+};
+
+/// State of a link before the write that made version `until`, so it is
+/// what snapshots older than `until` see.
+#[derive(Debug)]
+struct Version<T> {
+    until: u64,
+    link: Option<Vec<T>>,
+}
+
+#[derive(Debug)]
+struct State<T, L> {
+    links: L,
+    version: u64,
+    /// Older versions of each link changed since the oldest snapshot,
+    /// ordered by `until`.
+    chains: BTreeMap<T, VecDeque<Version<T>>>,
+    /// Number of live snapshots of each version.
+    snapshots: BTreeMap<u64, usize>,
+}
+
+impl<T: LinkType, L: Links<T>> State<T, L> {
+    /// Keeps the links that a write changed as they were before it.
+    fn keep(&mut self, changes: &[(Vec<T>, Vec<T>)], index_part: usize) {
+        if self.snapshots.is_empty() {
+            return;
+        }
+        for (before, after) in changes {
+            let index = before.get(index_part).or_else(|| after.get(index_part));
+            let Some(&index) = index else { continue };
+            let version = self.version;
+            let chain = self.chains.entry(index).or_default();
+            // Only the first change of a write shows what older snapshots saw.
+            if !matches!(chain.back(), Some(old) if old.until == version) {
+                chain.push_back(Version {
+                    until: version,
+                    link: (!before.is_empty()).then(|| before.clone()),
+                });
+            }
+        }
+    }
+
+    /// Returns the link at `index` as of `version`, or `None` if it has not
+    /// changed since.
+    fn old(&self, index: T, version: u64) -> Option<Option<&Vec<T>>> {
+        let chain = self.chains.get(&index)?;
+        let old = chain.iter().find(|old| old.until > version)?;
+        Some(old.link.as_ref())
+    }
+
+    /// Drops the versions that no snapshot can see anymore.
+    fn reclaim(&mut self) {
+        match self.snapshots.keys().next() {
+            None => self.chains.clear(),
+            Some(&oldest) => self.chains.retain(|_, chain| {
+                while chain.front().is_some_and(|old| old.until <= oldest) {
+                    chain.pop_front();
+                }
+                !chain.is_empty()
+            }),
+        }
+    }
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    // A panic in a previous call leaves the storage as the panic found it,
+    // as it would without the lock.
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Decorator that keeps old versions of links for [`Snapshot`]s.
+///
+/// Every write advances the version of the storage. While snapshots exist,
+/// the write keeps the `before` side of each change its handler reports, so
+/// a [`snapshot`](Self::snapshot) reads links as they were when it was taken,
+/// whatever writes come after. Versions are reclaimed as soon as the last
+/// snapshot that can see them is dropped, and none are kept while there are
+/// no snapshots.
+///
+/// A snapshot takes the read lock for each call only while it copies the
+/// matching links, so writes go through between calls and while handlers run.
+/// A query for a concrete index looks up that one link, and
+/// [`count_links`](Links::count_links) only visits the links changed since the
+/// snapshot, but a scan copies every match before the handler sees the first,
+/// as with [`SharedLinks`](crate::SharedLinks). Handlers may call back into the
+/// storage.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{Link, LinksExt, MemoryLinks, VersionedLinks};
+///
+/// let mut links = VersionedLinks::new(MemoryLinks::<u64>::new());
+/// let a = links.create_link(100, 200).unwrap();
+///
+/// let snapshot = links.snapshot();
+/// links.update(a, 300, 400).unwrap();
+/// let b = links.create_link(500, 600).unwrap();
+///
+/// assert_eq!(snapshot.get_link(a), Some(Link::new(a, 100, 200)));
+/// assert_eq!(snapshot.get_link(b), None);
+/// assert_eq!(links.get_link(a), Some(Link::new(a, 300, 400)));
+/// ```
+#[derive(Debug)]
+pub struct VersionedLinks<T: LinkType, L> {
+    state: Arc<RwLock<State<T, L>>>,
+    constants: LinksConstants<T>,
+}
+
+impl<T: LinkType, L: Links<T>> VersionedLinks<T, L> {
+    pub fn new(links: L) -> Self {
+        Self {
+            constants: links.constants_links(),
+            state: Arc::new(RwLock::new(State {
+                links,
+                version: 0,
+                chains: BTreeMap::new(),
+                snapshots: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Returns the number of writes made so far.
+    pub fn version(&self) -> u64 {
+        read(&self.state).version
+    }
+
+    /// Returns a read-only view of the links as they are now.
+    pub fn snapshot(&self) -> Snapshot<T, L> {
+        let mut state = write(&self.state);
+        let version = state.version;
+        *state.snapshots.entry(version).or_default() += 1;
+        Snapshot {
+            state: Arc::clone(&self.state),
+            constants: self.constants.clone(),
+            version,
+        }
+    }
+
+    /// Returns the storage, or `self` back while snapshots exist.
+    pub fn into_inner(self) -> Result<L, Self> {
+        match Arc::try_unwrap(self.state) {
+            Ok(state) => Ok(state
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .links),
+            Err(state) => Err(Self {
+                state,
+                constants: self.constants,
+            }),
+        }
+    }
+
+    fn record(
+        &self,
+        operation: impl for<'a> FnOnce(&'a mut L, WriteHandler<'_, T>) -> Result<Flow, Error<'a, T>>,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'static, T>> {
+        let mut changes = Vec::new();
+        let result = {
+            let mut state = write(&self.state);
+            state.version += 1;
+            let result = operation(&mut state.links, &mut |before, after| {
+                changes.push((before.to_vec(), after.to_vec()));
+                Flow::Continue
+            })
+            .map_err(Error::into_owned);
+            state.keep(&changes, self.constants.index_part.as_usize());
+            result
+        };
+        replay(&changes, |(before, after)| handler(before, after), result)
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for VersionedLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        read(&self.state).links.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.record(|links, handler| links.create_links(query, handler), handler)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        let (mut found, mut stride) = (Vec::new(), 0);
+        let result = read(&self.state)
+            .links
+            .each_links(query, &mut |link| {
+                found.extend_from_slice(link);
+                stride = link.len();
+                Flow::Continue
+            })
+            .map_err(Error::into_owned);
+        replay(found.chunks(stride.max(1)), handler, result)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.record(
+            |links, handler| links.update_links(query, replacement, handler),
+            handler,
+        )
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.record(|links, handler| links.delete_links(query, handler), handler)
+    }
+}
+
+/// Read-only view of a [`VersionedLinks`] at the version it was taken.
+///
+/// Reads combine the live storage with the versions the writes since have
+/// kept, and writes fail with [`Error::ReadOnly`]. Cloning a snapshot is
+/// cheap, and the versions it needs live until its last clone is dropped.
+#[derive(Debug)]
+pub struct Snapshot<T: LinkType, L: Links<T>> {
+    state: Arc<RwLock<State<T, L>>>,
+    constants: LinksConstants<T>,
+    version: u64,
+}
+
+impl<T: LinkType, L: Links<T>> Snapshot<T, L> {
+    /// Returns the version of the storage the snapshot shows.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn matches(&self, query: &[T], link: &[T]) -> bool {
+        let constants = &self.constants;
+        matches(
+            constants,
+            query,
+            link[constants.index_part.as_usize()],
+            link[constants.source_part.as_usize()],
+            link[constants.target_part.as_usize()],
+        )
+    }
+
+    /// Copies the links matching `query` as of the snapshot one after another,
+    /// the live ones unchanged since into `live` and the kept ones into `old`
+    /// by address, and returns the length of a link.
+    fn scan(
+        &self,
+        query: &[T],
+        live: &mut Vec<T>,
+        old: &mut Vec<T>,
+    ) -> Result<usize, Error<'static, T>> {
+        let index = self.constants.index_part.as_usize();
+        let state = read(&self.state);
+        let mut stride = 0;
+        let mut copy = |buffer: &mut Vec<T>, link: &[T]| {
+            buffer.extend_from_slice(link);
+            stride = link.len();
+        };
+        match query.first() {
+            Some(&address) if address != self.constants.any => {
+                match state.old(address, self.version) {
+                    Some(Some(link)) if self.matches(query, link) => copy(old, link),
+                    Some(_) => {}
+                    None => {
+                        state
+                            .links
+                            .each_links(query, &mut |link| {
+                                copy(live, link);
+                                Flow::Break
+                            })
+                            .map_err(Error::into_owned)?;
+                    }
+                }
+            }
+            _ => {
+                state
+                    .links
+                    .each_links(query, &mut |link| {
+                        if state.old(link[index], self.version).is_none() {
+                            copy(live, link);
+                        }
+                        Flow::Continue
+                    })
+                    .map_err(Error::into_owned)?;
+                for &address in state.chains.keys() {
+                    if let Some(Some(link)) = state.old(address, self.version) {
+                        if self.matches(query, link) {
+                            copy(old, link);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(stride)
+    }
+
+    /// Returns whether the live link at `address` matches `query`.
+    fn live_matches(&self, links: &L, query: &[T], address: T) -> bool {
+        let mut found = false;
+        let _ = links.each_links(&[address], &mut |link| {
+            found = self.matches(query, link);
+            Flow::Break
+        });
+        found
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Clone for Snapshot<T, L> {
+    fn clone(&self) -> Self {
+        *write(&self.state)
+            .snapshots
+            .entry(self.version)
+            .or_default() += 1;
+        Self {
+            state: Arc::clone(&self.state),
+            constants: self.constants.clone(),
+            version: self.version,
+        }
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Drop for Snapshot<T, L> {
+    fn drop(&mut self) {
+        let mut state = write(&self.state);
+        if let Some(count) = state.snapshots.get_mut(&self.version) {
+            *count -= 1;
+            if *count == 0 {
+                state.snapshots.remove(&self.version);
+                state.reclaim();
+            }
+        }
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for Snapshot<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        // Only the links changed since the snapshot differ from the live count.
+        let state = read(&self.state);
+        let (mut kept, mut changed) = (0usize, 0usize);
+        for &address in state.chains.keys() {
+            if let Some(old) = state.old(address, self.version) {
+                if old.is_some_and(|link| self.matches(query, link)) {
+                    kept += 1;
+                }
+                if self.live_matches(&state.links, query, address) {
+                    changed += 1;
+                }
+            }
+        }
+        let live = state.links.count_links(query).as_usize();
+        T::try_from(live + kept - changed).unwrap_or(T::MAX)
+    }
+
+    fn create_links(&mut self, _: &[T], _: WriteHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        Err(Error::ReadOnly)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        let (mut live, mut old) = (Vec::new(), Vec::new());
+        let stride = self.scan(query, &mut live, &mut old)?.max(1);
+        // Kept links go before the first live link with a higher address, so
+        // the links come in address order whenever the storage scans in it.
+        let index = self.constants.index_part.as_usize();
+        let (mut live, mut old) = (
+            live.chunks(stride).peekable(),
+            old.chunks(stride).peekable(),
+        );
+        let links = iter::from_fn(|| match (live.peek(), old.peek()) {
+            (Some(next), Some(kept)) if kept[index] < next[index] => old.next(),
+            (Some(_), _) => live.next(),
+            (None, _) => old.next(),
+        });
+        replay(links, handler, Ok(Flow::Continue))
+    }
+
+    fn update_links(
+        &mut self,
+        _: &[T],
+        _: &[T],
+        _: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        Err(Error::ReadOnly)
+    }
+
+    fn delete_links(&mut self, _: &[T], _: WriteHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        Err(Error::ReadOnly)
+    }
+}
@@ -2,6 +2,7 @@
 ///
 /// This is useful for creating queries or representing repeated elements
 /// without allocating a vector.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point<T> {
     index: T,
     size: usize,
@@ -1,5 +1,6 @@
+use alloc::vec::Vec;
 use beef::lean::Cow;
-use std::{ops::Index, slice::SliceIndex};
+use core::{ops::Index, slice::SliceIndex};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Query<'a, T: Clone>(Cow<'a, [T]>);
@@ -37,6 +38,22 @@ impl<'a, I: SliceIndex<[T]>, T: Clone> Index<I> for Query<'a, T> {
     }
 }
 
+/// Serialized as a sequence of its parts.
+#[cfg(feature = "serde")]
+impl<T: Clone + serde::Serialize> serde::Serialize for Query<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserialized into an owned query, whatever its lifetime.
+#[cfg(feature = "serde")]
+impl<'de, T: Clone + serde::Deserialize<'de>> serde::Deserialize<'de> for Query<'_, T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Query::new)
+    }
+}
+
 pub trait ToQuery<T: Clone> {
     fn to_query(&self) -> Query<'_, T>;
 }
@@ -0,0 +1,173 @@
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{Error, Flow, LinkType, Links, LinksConstants, ReadHandler, WriteHandler};
+
+/// Cloneable handle to a [`Links`] storage shared between threads.
+///
+/// Reads take a shared lock, so [`each_links`](Links::each_links) and
+/// [`count_links`](Links::count_links) run concurrently, while writes take the
+/// lock exclusively and run one at a time.
+///
+/// Handlers never run under the lock: each call records what its handler
+/// would receive, releases the lock, then calls the handler with the
+/// records. A handler may therefore read or write the storage through any
+/// handle, at the price of seeing links as they were when the call ran.
+/// A `Flow::Break` from the handler stops the remaining calls and is
+/// returned, but, as with any storage, does not undo a write.
+///
+/// A read therefore copies every matching link into one buffer before the
+/// handler sees the first, which costs time and memory in the number of
+/// matches; a query for a concrete index stops at the link it names.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{LinksExt, MemoryLinks, SharedLinks};
+/// use std::thread;
+///
+/// let links = SharedLinks::new(MemoryLinks::<u64>::new());
+/// let writers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let mut links = links.clone();
+///         thread::spawn(move || links.create_link(100, 200).unwrap())
+///     })
+///     .collect();
+/// for writer in writers {
+///     writer.join().unwrap();
+/// }
+/// assert_eq!(links.count_all(), 4);
+/// ```
+#[derive(Debug)]
+pub struct SharedLinks<T: LinkType, L> {
+    links: Arc<RwLock<L>>,
+    constants: LinksConstants<T>,
+}
+
+impl<T: LinkType, L: Links<T>> SharedLinks<T, L> {
+    pub fn new(links: L) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links: Arc::new(RwLock::new(links)),
+        }
+    }
+
+    /// Returns the storage, or this handle back while other handles exist.
+    pub fn into_inner(self) -> Result<L, Self> {
+        match Arc::try_unwrap(self.links) {
+            Ok(links) => Ok(links.into_inner().unwrap_or_else(PoisonError::into_inner)),
+            Err(links) => Err(Self {
+                links,
+                constants: self.constants,
+            }),
+        }
+    }
+
+    // A panic in a previous call leaves the storage as the panic found it,
+    // as it would without the lock.
+    fn read(&self) -> RwLockReadGuard<'_, L> {
+        self.links.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, L> {
+        self.links.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs `operation` under the write lock, then replays its handler calls
+    /// to `handler`.
+    fn record(
+        &self,
+        operation: impl for<'a> FnOnce(&'a mut L, WriteHandler<'_, T>) -> Result<Flow, Error<'a, T>>,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'static, T>> {
+        let mut calls = Vec::new();
+        let result = operation(&mut self.write(), &mut |before, after| {
+            calls.push((before.to_vec(), after.to_vec()));
+            Flow::Continue
+        })
+        .map_err(Error::into_owned);
+        replay(&calls, |(before, after)| handler(before, after), result)
+    }
+}
+
+/// Calls `handler` with `records` taken under a lock, once it is released,
+/// until it breaks. Returns its break, or else `result`.
+pub(crate) fn replay<T: LinkType, R>(
+    records: impl IntoIterator<Item = R>,
+    mut handler: impl FnMut(R) -> Flow,
+    result: Result<Flow, Error<'static, T>>,
+) -> Result<Flow, Error<'static, T>> {
+    if records.into_iter().any(|record| handler(record).is_break()) {
+        result.map(|_| Flow::Break)
+    } else {
+        result
+    }
+}
+
+impl<T: LinkType, L> Clone for SharedLinks<T, L> {
+    fn clone(&self) -> Self {
+        Self {
+            links: Arc::clone(&self.links),
+            constants: self.constants.clone(),
+        }
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for SharedLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.read().count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.record(|links, handler| links.create_links(query, handler), handler)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        // A concrete index names at most one link, so the scan stops there.
+        let single = query
+            .first()
+            .is_some_and(|&index| index != self.constants.any);
+        let (mut found, mut stride) = (Vec::new(), 0);
+        let result = self
+            .read()
+            .each_links(query, &mut |link| {
+                found.extend_from_slice(link);
+                stride = link.len();
+                if single {
+                    Flow::Break
+                } else {
+                    Flow::Continue
+                }
+            })
+            .map(|flow| if single { Flow::Continue } else { flow })
+            .map_err(Error::into_owned);
+        replay(found.chunks(stride.max(1)), handler, result)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.record(
+            |links, handler| links.update_links(query, replacement, handler),
+            handler,
+        )
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.record(|links, handler| links.delete_links(query, handler), handler)
+    }
+}
@@ -0,0 +1,176 @@
+use alloc::{borrow::Cow, vec, vec::Vec};
+
+use crate::{Error, Flow, LinkType, LinksConstants, SizeBalancedTree, TreeNodes};
+
+/// Returns `true` if the link `index: source -> target` matches `query`, with
+/// the semantics documented on [`MemoryLinks`](crate::MemoryLinks).
+pub(crate) fn matches<T: LinkType>(
+    constants: &LinksConstants<T>,
+    query: &[T],
+    index: T,
+    source: T,
+    target: T,
+) -> bool {
+    let any = constants.any;
+    let is = |value: T, expected: T| expected == any || value == expected;
+    let part = |part: T| query.get(part.as_usize()).copied().unwrap_or(any);
+
+    match query.len() {
+        0 => true,
+        1 => is(index, query[0]),
+        2 => {
+            is(index, part(constants.index_part)) && (is(source, query[1]) || is(target, query[1]))
+        }
+        _ => {
+            is(index, part(constants.index_part))
+                && is(source, part(constants.source_part))
+                && is(target, part(constants.target_part))
+        }
+    }
+}
+
+/// Query evaluation shared by the stores that index links with [`SizeBalancedTree`]s.
+///
+/// Implementors only describe where links and tree nodes live, and get the
+/// query semantics documented on [`MemoryLinks`](crate::MemoryLinks) for free.
+pub(crate) trait Store<T: LinkType> {
+    type Nodes<'a>: TreeNodes<T>
+    where
+        Self: 'a;
+
+    fn constants(&self) -> &LinksConstants<T>;
+
+    /// Returns the number of stored links.
+    fn total(&self) -> T;
+
+    /// Returns the source and target of an existing link.
+    fn get(&self, index: T) -> Option<(T, T)>;
+
+    fn sources(&self) -> (SizeBalancedTree<T>, Self::Nodes<'_>);
+
+    fn targets(&self) -> (SizeBalancedTree<T>, Self::Nodes<'_>);
+
+    /// Calls `handler` for every stored link in address order.
+    fn scan(&self, handler: &mut dyn FnMut(T, T, T) -> Flow) -> Flow;
+
+    fn part(&self, query: &[T], part: T) -> T {
+        query
+            .get(part.as_usize())
+            .copied()
+            .unwrap_or(self.constants().any)
+    }
+
+    fn repr(&self, buf: &mut [T], index: T, source: T, target: T) {
+        let constants = self.constants();
+        buf[constants.index_part.as_usize()] = index;
+        buf[constants.source_part.as_usize()] = source;
+        buf[constants.target_part.as_usize()] = target;
+    }
+
+    fn buf(&self) -> Vec<T> {
+        let constants = self.constants();
+        vec![constants.null; constants.target_part.as_usize() + 1]
+    }
+
+    fn matches(&self, query: &[T], index: T, source: T, target: T) -> bool {
+        matches(self.constants(), query, index, source, target)
+    }
+
+    /// Splits `query` into the source and target it restricts, unless it is
+    /// a two-part query, which is returned as `Err(value)`.
+    fn restriction(&self, query: &[T]) -> Result<(T, T), T> {
+        let constants = self.constants();
+        let any = constants.any;
+        match query.len() {
+            0 | 1 => Ok((any, any)),
+            2 if query[1] == any => Ok((any, any)),
+            2 => Err(query[1]),
+            _ => Ok((
+                self.part(query, constants.source_part),
+                self.part(query, constants.target_part),
+            )),
+        }
+    }
+
+    fn count(&self, query: &[T]) -> T {
+        let any = self.constants().any;
+        let index = self.part(query, self.constants().index_part);
+        if index != any {
+            let matches = self
+                .get(index)
+                .is_some_and(|(source, target)| self.matches(query, index, source, target));
+            return if matches { T::funty(1) } else { T::funty(0) };
+        }
+
+        let (source_tree, sources) = self.sources();
+        let (target_tree, targets) = self.targets();
+        match self.restriction(query) {
+            Err(value) => {
+                source_tree.count(&sources, value) + target_tree.count(&targets, value)
+                    - source_tree.count_pair(&sources, value, value)
+            }
+            Ok((source, target)) => match (source == any, target == any) {
+                (true, true) => self.total(),
+                (false, true) => source_tree.count(&sources, source),
+                (true, false) => target_tree.count(&targets, target),
+                (false, false) => source_tree.count_pair(&sources, source, target),
+            },
+        }
+    }
+
+    fn each(&self, query: &[T], handler: &mut dyn FnMut(T, T, T) -> Flow) -> Flow {
+        let any = self.constants().any;
+        let index = self.part(query, self.constants().index_part);
+        if index != any {
+            return match self.get(index) {
+                Some((source, target)) if self.matches(query, index, source, target) => {
+                    handler(index, source, target)
+                }
+                _ => Flow::Continue,
+            };
+        }
+
+        let (source_tree, sources) = self.sources();
+        let (target_tree, targets) = self.targets();
+        let mut visit = |index: T| {
+            let (source, target) = self.get(index).unwrap();
+            handler(index, source, target)
+        };
+        match self.restriction(query) {
+            Err(value) => {
+                if source_tree.each(&sources, value, &mut visit).is_break() {
+                    return Flow::Break;
+                }
+                target_tree.each(&targets, value, &mut |index| match self.get(index) {
+                    Some((source, _)) if source == value => Flow::Continue,
+                    _ => visit(index),
+                })
+            }
+            Ok((source, target)) => match (source == any, target == any) {
+                (true, true) => self.scan(handler),
+                (false, true) => source_tree.each(&sources, source, &mut visit),
+                (true, false) => target_tree.each(&targets, target, &mut visit),
+                (false, false) => source_tree.each_pair(&sources, source, target, &mut visit),
+            },
+        }
+    }
+
+    /// Fails with [`Error::HasUsages`] if other links refer to `index`.
+    fn check_usages(&self, index: T) -> Result<(), Error<'static, T>> {
+        let mut usages = Vec::new();
+        let query = [self.constants().any, index];
+        self.each(&query, &mut |usage, source, target| {
+            if usage != index {
+                let mut link = self.buf();
+                self.repr(&mut link, usage, source, target);
+                usages.push(Cow::Owned(link));
+            }
+            Flow::Continue
+        });
+        if usages.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::HasUsages(usages))
+        }
+    }
+}
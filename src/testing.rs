@@ -0,0 +1,531 @@
+//! Conformance checks for [`Links`] implementations.
+//!
+//! Available with the `testing` feature. Every check panics on the first broken
+//! expectation, so a backend can run the whole battery from one test:
+//!
+//! ```
+//! use platform_data::{testing, MemoryLinks};
+//!
+//! testing::run(MemoryLinks::<u32>::new);
+//! ```
+//!
+//! The checks describe the contract that [`MemoryLinks`](crate::MemoryLinks) and
+//! [`FileLinks`](crate::FileLinks) follow:
+//!
+//! - `create_links` takes a free address from the internal range, or exactly
+//!   `query[index_part]` when the query names one, failing with
+//!   [`Error::AlreadyExists`] if it is taken;
+//! - `update_links` and `delete_links` fail with [`Error::NotExists`] for a
+//!   missing link, and `delete_links` fails with [`Error::HasUsages`] while other
+//!   links refer to the deleted one;
+//! - write handlers receive `(&[], after)`, `(before, after)` and `(before, &[])`
+//!   respectively, and every method returns the [`Flow`] of its handler.
+//!
+//! Checks that take a storage expect it to be empty.
+
+use std::{
+    collections::{btree_map::Entry, hash_map::RandomState, BTreeMap},
+    fmt::Debug,
+    hash::{BuildHasher, Hasher},
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{write_handler, Error, Flow, Link, LinkType, Links, LinksConstants, LinksExt};
+
+/// Runs every check, each on a fresh storage made by `factory`.
+pub fn run<T: LinkType, L: Links<T>>(mut factory: impl FnMut() -> L) {
+    crud(&mut factory());
+    wildcards(&mut factory());
+    not_exists(&mut factory());
+    already_exists(&mut factory());
+    has_usages(&mut factory());
+    handlers(&mut factory());
+    flow(&mut factory());
+    sequences(factory, 100);
+}
+
+/// Creates, reads, updates and deletes links one at a time.
+pub fn crud<T: LinkType, L: Links<T> + ?Sized>(links: &mut L) {
+    let null = links.constants_links().null;
+    assert_eq!(links.count_all(), T::funty(0), "storage is not empty");
+
+    let a = links.create().expect("create a link");
+    assert!(
+        links.constants_links().is_internal(a),
+        "{} is not internal",
+        a
+    );
+    assert_eq!(links.get_link(a), Some(Link::new(a, null, null)));
+    let b = links.create().expect("create a link");
+    assert_ne!(a, b, "two links share an address");
+    assert_eq!(links.count_all(), T::funty(2));
+
+    links.update(a, a, b).expect("update a link");
+    assert_eq!(links.get_link(a), Some(Link::new(a, a, b)));
+    assert_eq!(links.get_link(b), Some(Link::new(b, null, null)));
+    assert_eq!(links.search(a, b), Some(a));
+
+    links.delete(a).expect("delete a link");
+    assert_eq!(links.get_link(a), None);
+    assert_eq!(links.search(a, b), None);
+    assert_eq!(links.count_all(), T::funty(1));
+    links.delete(b).expect("delete a link");
+    assert_eq!(all(links), []);
+}
+
+/// Queries links with `constants.any` in every position.
+pub fn wildcards<T: LinkType, L: Links<T> + ?Sized>(links: &mut L) {
+    let constants = links.constants_links();
+    let any = constants.any;
+    let a = links.create().expect("create a link");
+    let b = links.create().expect("create a link");
+    let c = links.create().expect("create a link");
+    links.update(a, a, a).expect("update a link");
+    links.update(b, a, b).expect("update a link");
+    links.update(c, b, a).expect("update a link");
+
+    let full = |index, source, target| Link::new(index, source, target).to_vec(&constants);
+    let cases = [
+        (vec![], vec![a, b, c]),
+        (vec![any], vec![a, b, c]),
+        (vec![b], vec![b]),
+        (vec![any, a], vec![a, b, c]),
+        (vec![any, b], vec![b, c]),
+        (vec![c, a], vec![c]),
+        (vec![any, c], vec![]),
+        (full(any, any, any), vec![a, b, c]),
+        (full(any, a, any), vec![a, b]),
+        (full(any, any, a), vec![a, c]),
+        (full(any, a, b), vec![b]),
+        (full(b, any, any), vec![b]),
+        (full(b, b, any), vec![]),
+    ];
+    for (query, expected) in cases.iter() {
+        let mut found = vec![];
+        let flow = links.each_links(query, &mut |link| {
+            found.push(link[constants.index_part.as_usize()]);
+            Flow::Continue
+        });
+        assert!(matches!(flow, Ok(Flow::Continue)), "query {:?}", query);
+        found.sort_unstable();
+        assert_eq!(&found, expected, "links matching {:?}", query);
+        assert_eq!(
+            links.count_links(query),
+            T::try_from(expected.len()).unwrap(),
+            "count of {:?}",
+            query
+        );
+    }
+}
+
+/// Updates and deletes links that do not exist.
+pub fn not_exists<T: LinkType, L: Links<T> + ?Sized>(links: &mut L) {
+    let index = links.create().expect("create a link");
+    links.delete(index).expect("delete a link");
+
+    assert_eq!(links.get_link(index), None);
+    assert_eq!(links.count_links(&[index]), T::funty(0));
+    assert!(
+        matches!(links.update(index, index, index), Err(Error::NotExists(missing)) if missing == index),
+        "updating a deleted link"
+    );
+    assert!(
+        matches!(links.delete(index), Err(Error::NotExists(missing)) if missing == index),
+        "deleting a deleted link"
+    );
+    assert_eq!(all(links), []);
+}
+
+/// Creates links at chosen addresses, including taken ones.
+pub fn already_exists<T: LinkType, L: Links<T> + ?Sized>(links: &mut L) {
+    let constants = links.constants_links();
+    let null = constants.null;
+    let a = links.create().expect("create a link");
+    // The first address past `a` that no link holds, leaving a gap if it can.
+    let mut chosen = a + T::funty(2);
+    while links.count_links(&[chosen]) != T::funty(0) {
+        chosen += T::funty(1);
+    }
+    assert!(constants.is_internal(chosen), "no free address after {}", a);
+
+    let mut created = None;
+    links
+        .create_links(&[chosen], &mut |_, after| {
+            created = Some(after[constants.index_part.as_usize()]);
+            Flow::Continue
+        })
+        .expect("create a link at a free address");
+    assert_eq!(created, Some(chosen));
+    assert_eq!(links.get_link(chosen), Some(Link::new(chosen, null, null)));
+
+    for &taken in [a, chosen].iter() {
+        let mut called = false;
+        let result = links.create_links(&[taken], &mut |_, _| {
+            called = true;
+            Flow::Continue
+        });
+        assert!(
+            matches!(result, Err(Error::AlreadyExists(ref index)) if **index == taken),
+            "creating a link at taken address {}",
+            taken
+        );
+        assert!(!called, "handler called for a failed create");
+    }
+    assert_eq!(links.count_all(), T::funty(2));
+}
+
+/// Deletes links that other links refer to.
+pub fn has_usages<T: LinkType, L: Links<T> + ?Sized>(links: &mut L) {
+    let constants = links.constants_links();
+    let a = links.create().expect("create a link");
+    let b = links.create().expect("create a link");
+    let c = links.create().expect("create a link");
+    links.update(a, a, a).expect("update a link");
+    links.update(b, a, c).expect("update a link");
+    links.update(c, b, a).expect("update a link");
+
+    match links.delete(a).map_err(Error::into_owned) {
+        Err(Error::HasUsages(usages)) => {
+            let mut usages: Vec<_> = usages
+                .iter()
+                .map(|usage| Link::from_slice(usage, &constants))
+                .collect();
+            usages.sort_by_key(|link| link.index);
+            assert_eq!(usages, [Link::new(b, a, c), Link::new(c, b, a)]);
+        }
+        other => panic!("deleting a used link returned {:?}", other),
+    }
+    assert_eq!(links.get_link(a), Some(Link::new(a, a, a)));
+    assert_eq!(links.count_all(), T::funty(3));
+
+    links.update(b, b, b).expect("update a link");
+    links.update(c, c, c).expect("update a link");
+    links.delete(a).expect("delete a self-referencing link");
+    assert_eq!(links.count_all(), T::funty(2));
+}
+
+/// Checks what write and read handlers receive.
+pub fn handlers<T: LinkType, L: Links<T> + ?Sized>(links: &mut L) {
+    let constants = links.constants_links();
+    let null = constants.null;
+    let mut calls = vec![];
+
+    links
+        .create_links(&[], &mut record(&constants, &mut calls))
+        .expect("create a link");
+    let index = match calls[..] {
+        [(before, after)] if before.is_nothing() => after.index,
+        _ => panic!("create handler received {:?}", calls),
+    };
+    let replacement = Link::new(index, index, null).to_vec(&constants);
+    links
+        .update_links(&[index], &replacement, &mut record(&constants, &mut calls))
+        .expect("update a link");
+    links
+        .delete_links(&[index], &mut record(&constants, &mut calls))
+        .expect("delete a link");
+    assert_eq!(
+        calls,
+        [
+            (Link::nothing(), Link::new(index, null, null)),
+            (Link::new(index, null, null), Link::new(index, index, null)),
+            (Link::new(index, index, null), Link::nothing()),
+        ]
+    );
+
+    let index = links.create().expect("create a link");
+    links.update(index, index, null).expect("update a link");
+    let mut read = vec![];
+    links
+        .each_links(&[index], &mut |link| {
+            read.push(Link::from_slice(link, &constants));
+            Flow::Continue
+        })
+        .expect("read a link");
+    assert_eq!(read, [Link::new(index, index, null)]);
+}
+
+/// Checks that [`Flow::Break`] stops iteration and is returned by every method.
+pub fn flow<T: LinkType, L: Links<T> + ?Sized>(links: &mut L) {
+    for _ in 0..3 {
+        links.create().expect("create a link");
+    }
+
+    let mut visited = 0;
+    let result = links.each_links(&[], &mut |_| {
+        visited += 1;
+        Flow::Break
+    });
+    assert!(
+        matches!(result, Ok(Flow::Break)),
+        "each_links after a break"
+    );
+    assert_eq!(visited, 1, "links visited after a break");
+
+    let mut visited = 0;
+    let result = links.each_links(&[], &mut |_| {
+        visited += 1;
+        Flow::Continue
+    });
+    assert!(
+        matches!(result, Ok(Flow::Continue)),
+        "each_links without a break"
+    );
+    assert_eq!(visited, 3);
+
+    let constants = links.constants_links();
+    let mut index = None;
+    let result = links.create_links(&[], &mut |_, after| {
+        index = Some(after[constants.index_part.as_usize()]);
+        Flow::Break
+    });
+    assert!(
+        matches!(result, Ok(Flow::Break)),
+        "create_links after a break"
+    );
+    let index = index.expect("create handler was not called");
+    let replacement = Link::new(index, index, index).to_vec(&constants);
+    let result = links.update_links(&[index], &replacement, &mut |_, _| Flow::Break);
+    assert!(
+        matches!(result, Ok(Flow::Break)),
+        "update_links after a break"
+    );
+    let result = links.delete_links(&[index], &mut |_, _| Flow::Break);
+    assert!(
+        matches!(result, Ok(Flow::Break)),
+        "delete_links after a break"
+    );
+    assert_eq!(
+        links.get_link(index),
+        None,
+        "a break must not undo a delete"
+    );
+}
+
+/// Applies `runs` random sequences of operations, each to a fresh storage,
+/// and compares the storage with a simple model after every step.
+///
+/// Operations use a handful of addresses at the start of the internal range,
+/// so creates, updates and deletes often collide and reach every error path.
+/// The sequences come from a random seed, which is printed when a check fails.
+pub fn sequences<T: LinkType, L: Links<T>>(factory: impl FnMut() -> L, runs: usize) {
+    let seed = RandomState::new().build_hasher().finish();
+    sequences_from_seed(factory, runs, seed);
+}
+
+/// Runs [`sequences`] from `seed`, such as one printed by a failed run.
+pub fn sequences_from_seed<T: LinkType, L: Links<T>>(
+    mut factory: impl FnMut() -> L,
+    runs: usize,
+    seed: u64,
+) {
+    let mut rng = Rng(seed);
+    for _ in 0..runs {
+        let len = rng.below(MAX_OPS + 1);
+        let ops: Vec<_> = (0..len).map(|_| Op::random(&mut rng)).collect();
+        let checked = panic::catch_unwind(AssertUnwindSafe(|| {
+            check_sequence(&mut factory(), &ops);
+        }));
+        if let Err(panic) = checked {
+            eprintln!("testing::sequences failed with seed {}", seed);
+            panic::resume_unwind(panic);
+        }
+    }
+}
+
+/// Number of addresses that [`sequences`] works with.
+const SPAN: u8 = 12;
+
+/// Length of the longest sequence that [`sequences`] applies.
+const MAX_OPS: u8 = 64;
+
+/// SplitMix64 generator, so that a seed always yields the same sequences.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u8) -> u8 {
+        (self.next() % u64::from(bound)) as u8
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Create,
+    CreateAt(u8),
+    Update(u8, u8, u8),
+    Delete(u8),
+}
+
+impl Op {
+    fn random(rng: &mut Rng) -> Self {
+        let mut value = || rng.below(SPAN);
+        match value() % 4 {
+            0 => Op::Create,
+            1 => Op::CreateAt(value()),
+            2 => Op::Update(value(), value(), value()),
+            _ => Op::Delete(value()),
+        }
+    }
+}
+
+fn check_sequence<T: LinkType, L: Links<T> + ?Sized>(links: &mut L, ops: &[Op]) {
+    let constants = links.constants_links();
+    let start = *constants.internal_range.start();
+    let address = |n: u8| start + T::funty(n);
+    let value = |n: u8| {
+        if n == 0 {
+            constants.null
+        } else {
+            address(n - 1)
+        }
+    };
+    let mut model = BTreeMap::new();
+
+    for op in ops {
+        let context = || format!("after {:?} in {:?}", op, ops);
+        match *op {
+            Op::Create => {
+                let index = links
+                    .create()
+                    .unwrap_or_else(|error| panic!("{:?} {}", error, context()));
+                assert!(
+                    !model.contains_key(&index),
+                    "reused {} {}",
+                    index,
+                    context()
+                );
+                assert!(constants.is_internal(index), "{} {}", index, context());
+                model.insert(index, (constants.null, constants.null));
+            }
+            Op::CreateAt(n) => {
+                let index = address(n);
+                let mut created = None;
+                let result = links
+                    .create_links(&[index], &mut |_, after| {
+                        created = Some(after[constants.index_part.as_usize()]);
+                        Flow::Continue
+                    })
+                    .map_err(Error::into_owned);
+                match model.entry(index) {
+                    Entry::Occupied(_) => expect_error(
+                        result,
+                        |error| matches!(error, Error::AlreadyExists(found) if **found == index),
+                        context,
+                    ),
+                    Entry::Vacant(entry) => {
+                        result.unwrap_or_else(|error| panic!("{:?} {}", error, context()));
+                        assert_eq!(created, Some(index), "{}", context());
+                        entry.insert((constants.null, constants.null));
+                    }
+                }
+            }
+            Op::Update(n, source, target) => {
+                let index = address(n);
+                let (source, target) = (value(source), value(target));
+                let result = links
+                    .update(index, source, target)
+                    .map_err(Error::into_owned);
+                if let Some(link) = model.get_mut(&index) {
+                    result.unwrap_or_else(|error| panic!("{:?} {}", error, context()));
+                    *link = (source, target);
+                } else {
+                    expect_error(
+                        result,
+                        |error| matches!(error, Error::NotExists(found) if *found == index),
+                        context,
+                    );
+                }
+            }
+            Op::Delete(n) => {
+                let index = address(n);
+                let usages: Vec<_> = model
+                    .iter()
+                    .filter(|&(&usage, &(source, target))| {
+                        usage != index && (source == index || target == index)
+                    })
+                    .map(|(&usage, &(source, target))| Link::new(usage, source, target))
+                    .collect();
+                let result = links.delete(index).map_err(Error::into_owned);
+                if !model.contains_key(&index) {
+                    expect_error(
+                        result,
+                        |error| matches!(error, Error::NotExists(found) if *found == index),
+                        context,
+                    );
+                } else if !usages.is_empty() {
+                    expect_error(
+                        result,
+                        |error| match error {
+                            Error::HasUsages(found) => {
+                                let mut found: Vec<_> = found
+                                    .iter()
+                                    .map(|usage| Link::from_slice(usage, &constants))
+                                    .collect();
+                                found.sort_by_key(|link| link.index);
+                                found == usages
+                            }
+                            _ => false,
+                        },
+                        context,
+                    );
+                } else {
+                    result.unwrap_or_else(|error| panic!("{:?} {}", error, context()));
+                    model.remove(&index);
+                }
+            }
+        }
+
+        let expected: Vec<_> = model
+            .iter()
+            .map(|(&index, &(source, target))| Link::new(index, source, target))
+            .collect();
+        assert_eq!(all(links), expected, "{}", context());
+        for n in 0..=SPAN {
+            let used = value(n);
+            let count = |query: Link<T>| links.count_links(&query.to_vec(&constants));
+            let sources = expected.iter().filter(|link| link.source == used).count();
+            let targets = expected.iter().filter(|link| link.target == used).count();
+            let source_count = count(Link::new(constants.any, used, constants.any));
+            let target_count = count(Link::new(constants.any, constants.any, used));
+            assert_eq!(source_count, T::try_from(sources).unwrap(), "{}", context());
+            assert_eq!(target_count, T::try_from(targets).unwrap(), "{}", context());
+        }
+    }
+}
+
+fn expect_error<T: LinkType, R: Debug>(
+    result: Result<R, Error<'static, T>>,
+    expected: impl FnOnce(&Error<'static, T>) -> bool,
+    context: impl FnOnce() -> String,
+) {
+    match result {
+        Err(error) if expected(&error) => {}
+        other => panic!("unexpected {:?} {}", other, context()),
+    }
+}
+
+/// Returns a write handler that records every call, with empty slices as
+/// [`Link::nothing`].
+fn record<'a, T: LinkType>(
+    constants: &LinksConstants<T>,
+    calls: &'a mut Vec<(Link<T>, Link<T>)>,
+) -> impl FnMut(&[T], &[T]) -> Flow + 'a {
+    write_handler(constants, move |before, after| {
+        calls.push((before, after));
+        Flow::Continue
+    })
+}
+
+fn all<T: LinkType, L: Links<T> + ?Sized>(links: &L) -> Vec<Link<T>> {
+    let mut all: Vec<_> = links.iter(&[]).expect("read all links").collect();
+    all.sort_by_key(|link| link.index);
+    all
+}
@@ -0,0 +1,218 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{
+    error, Change, Error, Flow, LinkType, Links, LinksConstants, ReadHandler, WriteHandler,
+};
+
+/// Position in a transaction that [`TransactionLinks::rollback_to`] can return to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Savepoint(u64);
+
+/// Failure of [`TransactionLinks`], reported as [`Error::Transaction`].
+#[derive(Debug)]
+pub enum TransactionError<T: LinkType> {
+    /// The savepoint was released, rolled back past or committed.
+    StaleSavepoint(Savepoint),
+
+    /// The inner storage failed to undo `change`.
+    Undo {
+        change: Change<T>,
+        error: Error<'static, T>,
+    },
+}
+
+// Written by hand: a derived impl would require `Error: Display`, whose
+// derived impl requires `TransactionError: Display` in turn.
+impl<T: LinkType> fmt::Display for TransactionError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StaleSavepoint(savepoint) => write!(
+                f,
+                "{:?} was released, rolled back past or committed",
+                savepoint
+            ),
+            Self::Undo { change, error } => write!(f, "unable to undo {:?}: {}", change, error),
+        }
+    }
+}
+
+impl<T: LinkType> error::Error for TransactionError<T> {}
+
+/// Decorator that records every mutation of the inner storage, so that a group
+/// of mutations can be committed or rolled back as a whole.
+///
+/// Mutations are applied to the inner storage immediately and recorded as the
+/// [`Change`]s their handlers receive. [`commit`](Self::commit) forgets them, and
+/// [`rollback`](Self::rollback) applies their inverses in reverse order. Undoing
+/// a deletion recreates the link at its address, so the inner storage has to
+/// support creating links at a chosen address, as [`MemoryLinks`](crate::MemoryLinks)
+/// and [`FileLinks`](crate::FileLinks) do.
+///
+/// Savepoints nest: rolling back to a savepoint undoes only the changes made
+/// after it and invalidates the savepoints taken later.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{LinksExt, MemoryLinks, TransactionLinks};
+///
+/// let mut links = TransactionLinks::new(MemoryLinks::<usize>::new());
+/// let a = links.create_link(1, 1).unwrap();
+/// links.commit();
+///
+/// let savepoint = links.savepoint();
+/// links.update(a, a, a).unwrap();
+/// let b = links.create().unwrap();
+/// links.rollback_to(savepoint).unwrap();
+///
+/// assert!(!links.exists(b));
+/// assert_eq!(links.search(1, 1), Some(a));
+/// ```
+#[derive(Debug)]
+pub struct TransactionLinks<T: LinkType, L> {
+    links: L,
+    constants: LinksConstants<T>,
+    changes: Vec<Change<T>>,
+    savepoints: Vec<(Savepoint, usize)>,
+    next_savepoint: u64,
+}
+
+impl<T: LinkType, L: Links<T>> TransactionLinks<T, L> {
+    /// Starts recording the mutations of `links`.
+    pub fn new(links: L) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links,
+            changes: Vec::new(),
+            savepoints: Vec::new(),
+            next_savepoint: 0,
+        }
+    }
+
+    /// Returns the changes made since the last commit or rollback, oldest first.
+    pub fn changes(&self) -> &[Change<T>] {
+        &self.changes
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.links
+    }
+
+    /// Returns the inner storage, keeping the changes that were not rolled back.
+    pub fn into_inner(self) -> L {
+        self.links
+    }
+
+    /// Keeps every change and drops all savepoints.
+    pub fn commit(&mut self) {
+        self.changes.clear();
+        self.savepoints.clear();
+    }
+
+    /// Undoes every change since the last commit and drops all savepoints.
+    ///
+    /// If the inner storage fails to undo a change, the changes that are
+    /// still applied stay recorded, so the rollback can be retried.
+    pub fn rollback(&mut self) -> Result<(), Error<'_, T>> {
+        self.savepoints.clear();
+        self.undo(0)
+    }
+
+    /// Marks the current position to return to with [`rollback_to`](Self::rollback_to).
+    pub fn savepoint(&mut self) -> Savepoint {
+        let savepoint = Savepoint(self.next_savepoint);
+        self.next_savepoint += 1;
+        self.savepoints.push((savepoint, self.changes.len()));
+        savepoint
+    }
+
+    /// Undoes the changes made after `savepoint`, which stays valid.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) -> Result<(), Error<'_, T>> {
+        let position = self.find(savepoint)?;
+        self.savepoints.truncate(position + 1);
+        self.undo(self.savepoints[position].1)
+    }
+
+    /// Forgets `savepoint` and the savepoints taken after it, keeping their changes.
+    pub fn release(&mut self, savepoint: Savepoint) -> Result<(), Error<'_, T>> {
+        let position = self.find(savepoint)?;
+        self.savepoints.truncate(position);
+        Ok(())
+    }
+
+    fn find(&self, savepoint: Savepoint) -> Result<usize, Error<'static, T>> {
+        self.savepoints
+            .iter()
+            .position(|&(taken, _)| taken == savepoint)
+            .ok_or_else(|| TransactionError::StaleSavepoint(savepoint).into())
+    }
+
+    fn undo(&mut self, position: usize) -> Result<(), Error<'_, T>> {
+        while self.changes.len() > position {
+            let change = self.changes[self.changes.len() - 1];
+            if let Err(error) = change
+                .inverse()
+                .apply(&mut self.links, &mut |_, _| Flow::Continue)
+            {
+                let error = error.into_owned();
+                return Err(TransactionError::Undo { change, error }.into());
+            }
+            self.changes.pop();
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn record<'a, T: LinkType>(
+    changes: &'a mut Vec<Change<T>>,
+    constants: &'a LinksConstants<T>,
+    handler: WriteHandler<'a, T>,
+) -> impl FnMut(&[T], &[T]) -> Flow + 'a {
+    move |before, after| {
+        changes.push(Change::from_slices(before, after, constants));
+        handler(before, after)
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for TransactionLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.links.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let handler = &mut record(&mut self.changes, &self.constants, handler);
+        self.links.create_links(query, handler)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        self.links.each_links(query, handler)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let handler = &mut record(&mut self.changes, &self.constants, handler);
+        self.links.update_links(query, replacement, handler)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let handler = &mut record(&mut self.changes, &self.constants, handler);
+        self.links.delete_links(query, handler)
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{Flow, LinkType};
+
+/// Node fields of a [`SizeBalancedTree`] stored alongside a link.
+///
+/// Children are link addresses, and zero stands for the empty subtree, so the
+/// internal range of a store that uses these trees must not contain zero.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TreeNode<T> {
+    pub left: T,
+    pub right: T,
+    pub size: T,
+}
+
+/// Read access to the nodes of an index.
+///
+/// A links store implements this once per index, usually as a thin view over
+/// its link records: the source index orders links by `(source, target)` and
+/// the target index by `(target, source)`.
+pub trait TreeNodes<T: LinkType> {
+    /// Returns the tree fields of `link`.
+    fn node(&self, link: T) -> TreeNode<T>;
+
+    /// Returns the `(primary, secondary)` key by which `link` is ordered.
+    fn key(&self, link: T) -> (T, T);
+}
+
+/// Write access to the nodes of an index.
+pub trait TreeNodesMut<T: LinkType>: TreeNodes<T> {
+    /// Replaces the tree fields of `link`.
+    fn set_node(&mut self, link: T, node: TreeNode<T>);
+}
+
+/// Size-balanced binary search tree of link addresses.
+///
+/// The tree itself only remembers its root, while nodes live in [`TreeNodes`],
+/// which lets the same algorithm index links kept in a vector or in a mapped
+/// file. Links are ordered by their key and then by address, so duplicate keys
+/// are allowed. Every operation takes logarithmic time, plus the number of
+/// visited links for [`each`](Self::each) and [`each_pair`](Self::each_pair).
+///
+/// The key of a link must not change while it is in the tree: remove it before
+/// updating the link and insert it again afterwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeBalancedTree<T> {
+    root: T,
+}
+
+impl<T: LinkType> SizeBalancedTree<T> {
+    /// Creates an empty tree.
+    pub fn new() -> Self {
+        Self::from_root(T::funty(0))
+    }
+
+    /// Restores a tree whose nodes are already in place.
+    pub const fn from_root(root: T) -> Self {
+        Self { root }
+    }
+
+    pub const fn root(&self) -> T {
+        self.root
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root == T::funty(0)
+    }
+
+    /// Returns the number of links in the tree.
+    pub fn len<N: TreeNodes<T>>(&self, nodes: &N) -> T {
+        size(nodes, self.root)
+    }
+
+    pub fn insert<N: TreeNodesMut<T>>(&mut self, nodes: &mut N, link: T) {
+        self.root = insert(nodes, self.root, link);
+    }
+
+    /// Removes `link`, which must be in the tree with its current key.
+    pub fn remove<N: TreeNodesMut<T>>(&mut self, nodes: &mut N, link: T) {
+        self.root = remove(nodes, self.root, link);
+    }
+
+    /// Counts links whose primary key is `primary`.
+    pub fn count<N: TreeNodes<T>>(&self, nodes: &N, primary: T) -> T {
+        self.count_range(nodes, (primary, T::funty(0)), (primary, T::MAX))
+    }
+
+    /// Counts links whose key is `(primary, secondary)`.
+    pub fn count_pair<N: TreeNodes<T>>(&self, nodes: &N, primary: T, secondary: T) -> T {
+        self.count_range(nodes, (primary, secondary), (primary, secondary))
+    }
+
+    /// Calls `handler` for links whose primary key is `primary`, in key order.
+    pub fn each<N: TreeNodes<T>>(
+        &self,
+        nodes: &N,
+        primary: T,
+        handler: &mut dyn FnMut(T) -> Flow,
+    ) -> Flow {
+        let range = (
+            (primary, T::funty(0), T::funty(0)),
+            (primary, T::MAX, T::MAX),
+        );
+        each(nodes, self.root, range, handler)
+    }
+
+    /// Calls `handler` for links whose key is `(primary, secondary)`, in address order.
+    pub fn each_pair<N: TreeNodes<T>>(
+        &self,
+        nodes: &N,
+        primary: T,
+        secondary: T,
+        handler: &mut dyn FnMut(T) -> Flow,
+    ) -> Flow {
+        let range = (
+            (primary, secondary, T::funty(0)),
+            (primary, secondary, T::MAX),
+        );
+        each(nodes, self.root, range, handler)
+    }
+
+    /// Returns the lowest address with key `(primary, secondary)`.
+    pub fn search<N: TreeNodes<T>>(&self, nodes: &N, primary: T, secondary: T) -> Option<T> {
+        let mut found = None;
+        self.each_pair(nodes, primary, secondary, &mut |link| {
+            found = Some(link);
+            Flow::Break
+        });
+        found
+    }
+
+    fn count_range<N: TreeNodes<T>>(&self, nodes: &N, from: (T, T), to: (T, T)) -> T {
+        let below = |bound: (T, T, T), inclusive: bool| {
+            let (mut node, mut count) = (self.root, T::funty(0));
+            while node != T::funty(0) {
+                let key = full_key(nodes, node);
+                if key < bound || inclusive && key == bound {
+                    count += size(nodes, nodes.node(node).left) + T::funty(1);
+                    node = nodes.node(node).right;
+                } else {
+                    node = nodes.node(node).left;
+                }
+            }
+            count
+        };
+        below((to.0, to.1, T::MAX), true) - below((from.0, from.1, T::funty(0)), false)
+    }
+}
+
+type Key<T> = (T, T, T);
+
+fn full_key<T: LinkType, N: TreeNodes<T>>(nodes: &N, link: T) -> Key<T> {
+    let (primary, secondary) = nodes.key(link);
+    (primary, secondary, link)
+}
+
+fn size<T: LinkType, N: TreeNodes<T>>(nodes: &N, link: T) -> T {
+    if link == T::funty(0) {
+        T::funty(0)
+    } else {
+        nodes.node(link).size
+    }
+}
+
+fn update<T: LinkType, N: TreeNodesMut<T>>(
+    nodes: &mut N,
+    link: T,
+    f: impl FnOnce(&mut TreeNode<T>),
+) {
+    let mut node = nodes.node(link);
+    f(&mut node);
+    nodes.set_node(link, node);
+}
+
+fn fix_size<T: LinkType, N: TreeNodesMut<T>>(nodes: &mut N, link: T) {
+    let node = nodes.node(link);
+    let size = size(nodes, node.left) + size(nodes, node.right) + T::funty(1);
+    update(nodes, link, |node| node.size = size);
+}
+
+fn rotate_left<T: LinkType, N: TreeNodesMut<T>>(nodes: &mut N, root: T) -> T {
+    let right = nodes.node(root).right;
+    let size = nodes.node(root).size;
+    let moved = nodes.node(right).left;
+    update(nodes, root, |node| node.right = moved);
+    update(nodes, right, |node| {
+        node.left = root;
+        node.size = size;
+    });
+    fix_size(nodes, root);
+    right
+}
+
+fn rotate_right<T: LinkType, N: TreeNodesMut<T>>(nodes: &mut N, root: T) -> T {
+    let left = nodes.node(root).left;
+    let size = nodes.node(root).size;
+    let moved = nodes.node(left).right;
+    update(nodes, root, |node| node.left = moved);
+    update(nodes, left, |node| {
+        node.right = root;
+        node.size = size;
+    });
+    fix_size(nodes, root);
+    left
+}
+
+fn maintain<T: LinkType, N: TreeNodesMut<T>>(nodes: &mut N, root: T, right_heavy: bool) -> T {
+    if root == T::funty(0) {
+        return root;
+    }
+
+    let node = nodes.node(root);
+    let (inner, outer) = if right_heavy {
+        (node.right, node.left)
+    } else {
+        (node.left, node.right)
+    };
+    if inner == T::funty(0) {
+        return root;
+    }
+
+    let outer_size = size(nodes, outer);
+    let (near, far) = if right_heavy {
+        (nodes.node(inner).left, nodes.node(inner).right)
+    } else {
+        (nodes.node(inner).right, nodes.node(inner).left)
+    };
+
+    let mut root = if size(nodes, far) > outer_size {
+        if right_heavy {
+            rotate_left(nodes, root)
+        } else {
+            rotate_right(nodes, root)
+        }
+    } else if size(nodes, near) > outer_size {
+        if right_heavy {
+            let right = rotate_right(nodes, inner);
+            update(nodes, root, |node| node.right = right);
+            rotate_left(nodes, root)
+        } else {
+            let left = rotate_left(nodes, inner);
+            update(nodes, root, |node| node.left = left);
+            rotate_right(nodes, root)
+        }
+    } else {
+        return root;
+    };
+
+    let left = maintain(nodes, nodes.node(root).left, false);
+    update(nodes, root, |node| node.left = left);
+    let right = maintain(nodes, nodes.node(root).right, true);
+    update(nodes, root, |node| node.right = right);
+    root = maintain(nodes, root, false);
+    maintain(nodes, root, true)
+}
+
+fn insert<T: LinkType, N: TreeNodesMut<T>>(nodes: &mut N, root: T, link: T) -> T {
+    if root == T::funty(0) {
+        nodes.set_node(
+            link,
+            TreeNode {
+                left: T::funty(0),
+                right: T::funty(0),
+                size: T::funty(1),
+            },
+        );
+        return link;
+    }
+
+    update(nodes, root, |node| node.size += T::funty(1));
+    let right_heavy = full_key(nodes, link) >= full_key(nodes, root);
+    if right_heavy {
+        let right = insert(nodes, nodes.node(root).right, link);
+        update(nodes, root, |node| node.right = right);
+    } else {
+        let left = insert(nodes, nodes.node(root).left, link);
+        update(nodes, root, |node| node.left = left);
+    }
+    maintain(nodes, root, right_heavy)
+}
+
+fn remove<T: LinkType, N: TreeNodesMut<T>>(nodes: &mut N, root: T, link: T) -> T {
+    let node = nodes.node(root);
+    if root == link {
+        if node.left == T::funty(0) {
+            return node.right;
+        }
+        if node.right == T::funty(0) {
+            return node.left;
+        }
+        let (left, max) = remove_max(nodes, node.left);
+        nodes.set_node(
+            max,
+            TreeNode {
+                left,
+                right: node.right,
+                size: node.size - T::funty(1),
+            },
+        );
+        return max;
+    }
+
+    update(nodes, root, |node| node.size -= T::funty(1));
+    if full_key(nodes, link) < full_key(nodes, root) {
+        let left = remove(nodes, node.left, link);
+        update(nodes, root, |node| node.left = left);
+    } else {
+        let right = remove(nodes, node.right, link);
+        update(nodes, root, |node| node.right = right);
+    }
+    root
+}
+
+fn remove_max<T: LinkType, N: TreeNodesMut<T>>(nodes: &mut N, root: T) -> (T, T) {
+    let node = nodes.node(root);
+    if node.right == T::funty(0) {
+        return (node.left, root);
+    }
+    update(nodes, root, |node| node.size -= T::funty(1));
+    let (right, max) = remove_max(nodes, node.right);
+    update(nodes, root, |node| node.right = right);
+    (root, max)
+}
+
+fn each<T: LinkType, N: TreeNodes<T>>(
+    nodes: &N,
+    root: T,
+    (from, to): (Key<T>, Key<T>),
+    handler: &mut dyn FnMut(T) -> Flow,
+) -> Flow {
+    if root == T::funty(0) {
+        return Flow::Continue;
+    }
+
+    let node = nodes.node(root);
+    let key = full_key(nodes, root);
+    if key > from && each(nodes, node.left, (from, to), handler).is_break() {
+        return Flow::Break;
+    }
+    if key >= from && key <= to && handler(root).is_break() {
+        return Flow::Break;
+    }
+    if key < to {
+        return each(nodes, node.right, (from, to), handler);
+    }
+    Flow::Continue
+}
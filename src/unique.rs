@@ -0,0 +1,227 @@
+use alloc::borrow::Cow;
+
+use crate::{
+    Error, Flow, Link, LinkType, Links, LinksConstants, LinksExt, ReadHandler, WriteHandler,
+};
+
+/// What [`UniqueLinks`] does when a link would duplicate an existing one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DuplicatePolicy {
+    /// Fail with [`Error::AlreadyExists`] holding the address of the existing link.
+    Reject,
+    /// Redirect the usages of the duplicate to the existing link and delete the
+    /// duplicate. Usages that become duplicates in turn are merged the same way.
+    Merge,
+}
+
+/// Decorator that keeps every `(source, target)` pair stored at most once.
+///
+/// Before an update, the inner storage is searched for another link with the
+/// new source and target, and [`DuplicatePolicy`] decides what happens if
+/// there is one. Links whose source and target are both `null` are
+/// placeholders and may repeat.
+///
+/// [`create_links`](Links::create_links) also accepts a full query, such as
+/// `[any, source, target]`, and then creates the link pointing to `source` and
+/// `target` only if no such link exists yet. A new link has no usages to
+/// redirect, so a duplicate is reported as [`Error::AlreadyExists`] under both
+/// policies. `any` as the source or target leaves it unspecified, and the
+/// link is created empty. [`LinksExt::create_link`] creates a placeholder first
+/// and deletes it again when the following update is rejected.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{DuplicatePolicy, Error, Flow, Links, LinksExt, MemoryLinks, UniqueLinks};
+///
+/// let mut links = UniqueLinks::new(MemoryLinks::<usize>::new(), DuplicatePolicy::Reject);
+/// let any = links.constants_links().any;
+/// let a = links.create_link(1, 2).unwrap();
+///
+/// let duplicate = links.create_links(&[any, 1, 2], &mut |_, _| Flow::Continue);
+/// assert!(matches!(duplicate, Err(Error::AlreadyExists(existing)) if *existing == a));
+///
+/// let b = links.create_link(a, a).unwrap();
+/// assert!(matches!(links.update(b, 1, 2), Err(Error::AlreadyExists(existing)) if *existing == a));
+/// assert_eq!(links.count_all(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct UniqueLinks<T: LinkType, L> {
+    links: L,
+    constants: LinksConstants<T>,
+    policy: DuplicatePolicy,
+}
+
+impl<T: LinkType, L: Links<T>> UniqueLinks<T, L> {
+    pub fn new(links: L, policy: DuplicatePolicy) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links,
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> DuplicatePolicy {
+        self.policy
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.links
+    }
+
+    pub fn into_inner(self) -> L {
+        self.links
+    }
+
+    /// Returns a link other than `index` from `source` to `target`.
+    fn duplicate(&self, index: T, source: T, target: T) -> Option<T> {
+        let null = self.constants.null;
+        if source == null && target == null {
+            return None;
+        }
+        let query = Link::new(self.constants.any, source, target).to_vec(&self.constants);
+        let mut found = None;
+        self.links
+            .each_links(&query, &mut |link| {
+                let link = link[self.constants.index_part.as_usize()];
+                if link == index {
+                    Flow::Continue
+                } else {
+                    found = Some(link);
+                    Flow::Break
+                }
+            })
+            .ok()?;
+        found
+    }
+
+    /// Returns a link other than `index` that refers to `index`.
+    fn usage(&self, index: T) -> Option<Link<T>> {
+        let mut found = None;
+        self.links
+            .each_links(&[self.constants.any, index], &mut |link| {
+                let link = Link::from_slice(link, &self.constants);
+                if link.index == index {
+                    Flow::Continue
+                } else {
+                    found = Some(link);
+                    Flow::Break
+                }
+            })
+            .ok()?;
+        found
+    }
+
+    /// Moves the usages of `duplicate` to `existing` and deletes `duplicate`.
+    fn merge(
+        &mut self,
+        duplicate: T,
+        existing: T,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'static, T>> {
+        let mut flow = Flow::Continue;
+        let redirect = |part: T| if part == duplicate { existing } else { part };
+        while let Some(usage) = self.usage(duplicate) {
+            let replacement =
+                Link::new(usage.index, redirect(usage.source), redirect(usage.target));
+            let replacement = replacement.to_vec(&self.constants);
+            if self
+                .update_links(&[usage.index], &replacement, handler)
+                .map_err(Error::into_owned)?
+                .is_break()
+            {
+                flow = Flow::Break;
+            }
+        }
+        if self
+            .links
+            .delete_links(&[duplicate], handler)
+            .map_err(Error::into_owned)?
+            .is_break()
+        {
+            flow = Flow::Break;
+        }
+        Ok(flow)
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for UniqueLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.links.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let (source, target) = match (
+            query.get(self.constants.source_part.as_usize()),
+            query.get(self.constants.target_part.as_usize()),
+        ) {
+            (Some(&source), Some(&target)) => (source, target),
+            _ => return self.links.create_links(query, handler),
+        };
+        // `any` leaves the source or target unspecified, as in a plain create.
+        let any = self.constants.any;
+        if source == any || target == any {
+            let index = query[self.constants.index_part.as_usize()];
+            return self.links.create_links(&[index], handler);
+        }
+        if let Some(existing) = self.duplicate(self.constants.any, source, target) {
+            return Err(Error::AlreadyExists(Cow::Owned(existing)));
+        }
+
+        let index_part = self.constants.index_part.as_usize();
+        let mut index = query[index_part];
+        let created = self
+            .links
+            .create_links(&[index], &mut |before, after| {
+                index = after[index_part];
+                handler(before, after)
+            })
+            .map_err(Error::into_owned)?;
+        let replacement = Link::new(index, source, target).to_vec(&self.constants);
+        let updated = self.links.update_links(&[index], &replacement, handler)?;
+        Ok(if created.is_break() { created } else { updated })
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        self.links.each_links(query, handler)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = query
+            .get(self.constants.index_part.as_usize())
+            .copied()
+            .unwrap_or(self.constants.any);
+        let source = replacement[self.constants.source_part.as_usize()];
+        let target = replacement[self.constants.target_part.as_usize()];
+        let existing = match self.duplicate(index, source, target) {
+            Some(existing) if self.links.exists(index) => existing,
+            _ => return self.links.update_links(query, replacement, handler),
+        };
+
+        match self.policy {
+            DuplicatePolicy::Reject => Err(Error::AlreadyExists(Cow::Owned(existing))),
+            DuplicatePolicy::Merge => self.merge(index, existing, handler),
+        }
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.links.delete_links(query, handler)
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{
+    Error, Flow, Link, LinkType, Links, LinksConstants, LinksExt, ReadHandler, WriteHandler,
+};
+use alloc::{
+    borrow::Cow,
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+
+/// What [`UsageLinks::delete_links`](Links::delete_links) does with the links
+/// that refer to the deleted one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeleteMode {
+    /// Fail with [`Error::HasUsages`] listing every link that refers to it.
+    Restrict,
+    /// Delete the links that refer to it, and the links that refer to those.
+    Cascade,
+    /// Point the references to it at `null`.
+    SetNull,
+}
+
+/// Decorator that resolves the usages of a link before deleting it.
+///
+/// A usage is a link other than the deleted one whose source or target is the
+/// deleted link, as returned by [`LinksExt::usages`]. Self-references never
+/// block a deletion. Every update and deletion made on the way is reported to
+/// the handler.
+///
+/// [`DeleteMode::Cascade`] deletes every link before the links it refers to.
+/// Links that refer to each other in a cycle have their references into the
+/// cycle pointed at `null` first, and the handler sees those updates too.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{DeleteMode, LinksExt, MemoryLinks, UsageLinks};
+///
+/// let mut links = UsageLinks::new(MemoryLinks::<usize>::new(), DeleteMode::SetNull);
+/// let a = links.create_link(100, 200).unwrap();
+/// let b = links.create_link(a, a).unwrap();
+///
+/// links.delete(a).unwrap();
+/// assert_eq!(links.get_link(b).map(|link| (link.source, link.target)), Some((0, 0)));
+///
+/// links.set_mode(DeleteMode::Cascade);
+/// let c = links.create_link(b, 100).unwrap();
+/// links.delete(b).unwrap();
+/// assert!(!links.exists(c));
+/// ```
+#[derive(Clone, Debug)]
+pub struct UsageLinks<T: LinkType, L> {
+    links: L,
+    constants: LinksConstants<T>,
+    mode: DeleteMode,
+}
+
+impl<T: LinkType, L: Links<T>> UsageLinks<T, L> {
+    pub fn new(links: L, mode: DeleteMode) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links,
+            mode,
+        }
+    }
+
+    pub fn mode(&self) -> DeleteMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: DeleteMode) {
+        self.mode = mode;
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.links
+    }
+
+    pub fn into_inner(self) -> L {
+        self.links
+    }
+
+    /// Points the parts of `link` that are in `targets` at `null`.
+    fn detach(
+        &mut self,
+        link: Link<T>,
+        targets: impl Fn(T) -> bool,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'static, T>> {
+        let null = self.constants.null;
+        let part = |part: T| if targets(part) { null } else { part };
+        let replacement = Link::new(link.index, part(link.source), part(link.target));
+        self.links
+            .update_links(&[link.index], &replacement.to_vec(&self.constants), handler)
+            .map_err(Error::into_owned)
+    }
+
+    fn set_null(
+        &mut self,
+        index: T,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'static, T>> {
+        let mut flow = Flow::Continue;
+        for usage in self.links.usages(index) {
+            if self
+                .detach(usage, |part| part == index, handler)?
+                .is_break()
+            {
+                flow = Flow::Break;
+            }
+        }
+        Ok(flow)
+    }
+
+    /// Deletes `index` and the links that depend on it, dependents first.
+    fn cascade(
+        &mut self,
+        index: T,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'static, T>> {
+        let root = self.links.get_link(index).ok_or(Error::NotExists(index))?;
+
+        // The doomed links in the order they are found, their positions in
+        // it, and how many other doomed links use each of them.
+        let mut doomed = vec![root];
+        let mut positions = BTreeMap::from([(root.index, 0)]);
+        let mut uses = Vec::new();
+        while uses.len() < doomed.len() {
+            let usages = self.links.usages(doomed[uses.len()].index);
+            uses.push(usages.len());
+            for usage in usages {
+                if let Entry::Vacant(entry) = positions.entry(usage.index) {
+                    entry.insert(doomed.len());
+                    doomed.push(usage);
+                }
+            }
+        }
+
+        // Links that no remaining link uses, deleted last found first.
+        let mut ready: BTreeSet<_> = (0..doomed.len()).filter(|&at| uses[at] == 0).collect();
+        let mut remaining: BTreeSet<_> = (0..doomed.len()).collect();
+        // Whether `part` of `link` refers to another doomed link.
+        let refers = |link: Link<T>, part: T| part != link.index && positions.contains_key(&part);
+        let mut flow = Flow::Continue;
+        loop {
+            let step = if let Some(at) = ready.pop_last() {
+                remaining.remove(&at);
+                let link = doomed[at];
+                let step = self
+                    .links
+                    .delete_links(&[link.index], handler)
+                    .map_err(Error::into_owned)?;
+                release(link, &positions, &mut uses, &mut ready);
+                step
+            } else if let Some(at) = remaining.iter().copied().find(|&at| {
+                let link = doomed[at];
+                refers(link, link.source) || refers(link, link.target)
+            }) {
+                // Every remaining link is used by another one, so some of
+                // them form a cycle. Detaching one of its links breaks it.
+                let link = doomed[at];
+                let step = self.detach(link, |part| refers(link, part), handler)?;
+                release(link, &positions, &mut uses, &mut ready);
+                let null = self.constants.null;
+                let part = |part: T| if refers(link, part) { null } else { part };
+                doomed[at] = Link::new(link.index, part(link.source), part(link.target));
+                step
+            } else {
+                break;
+            };
+            if step.is_break() {
+                flow = Flow::Break;
+            }
+        }
+        Ok(flow)
+    }
+}
+
+/// Drops the references of `link` to other doomed links, marking the links
+/// that are no longer used as ready to delete.
+fn release<T: LinkType>(
+    link: Link<T>,
+    positions: &BTreeMap<T, usize>,
+    uses: &mut [usize],
+    ready: &mut BTreeSet<usize>,
+) {
+    let parts = [link.source, link.target];
+    let parts = if link.source == link.target {
+        &parts[..1]
+    } else {
+        &parts[..]
+    };
+    for part in parts {
+        match positions.get(part) {
+            Some(&at) if *part != link.index => {
+                uses[at] -= 1;
+                if uses[at] == 0 {
+                    ready.insert(at);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for UsageLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.links.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.links.create_links(query, handler)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        self.links.each_links(query, handler)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.links.update_links(query, replacement, handler)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = query
+            .get(self.constants.index_part.as_usize())
+            .copied()
+            .unwrap_or(self.constants.any);
+        // Only a concrete address has usages to resolve; anything else is the
+        // inner storage's to reject.
+        let concrete = index != self.constants.any && self.constants.is_internal(index);
+        if !concrete || !self.links.exists(index) {
+            return self.links.delete_links(query, handler);
+        }
+
+        let resolved = match self.mode {
+            DeleteMode::Restrict => {
+                let usages = self.links.usages(index);
+                if !usages.is_empty() {
+                    let usages = usages
+                        .iter()
+                        .map(|usage| Cow::Owned(usage.to_vec(&self.constants)))
+                        .collect();
+                    return Err(Error::HasUsages(usages));
+                }
+                Flow::Continue
+            }
+            DeleteMode::SetNull => self.set_null(index, handler)?,
+            DeleteMode::Cascade => return self.cascade(index, handler),
+        };
+        let deleted = self.links.delete_links(query, handler)?;
+        Ok(if resolved.is_break() {
+            resolved
+        } else {
+            deleted
+        })
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{Error, Flow, LinkType, Links, LinksConstants, ReadHandler, WriteHandler};
+
+/// Failure of [`ValidatedLinks`], reported as [`Error::Invalid`].
+///
+/// `part` is the position of the offending value in the query or the
+/// replacement, as in [`LinksConstants::source_part`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationError<T: LinkType> {
+    #[error("{0} parts do not form a link")]
+    Length(usize),
+
+    #[error("part {part} is {value}, which is a reserved constant")]
+    Reserved { part: usize, value: T },
+
+    #[error("part {part} is {value}, which is not an internal address")]
+    Address { part: usize, value: T },
+
+    #[error("part {part} is {value}, which is an external reference, not an internal address")]
+    External { part: usize, value: T },
+
+    #[error(
+        "part {part} is {value}, which is neither an internal address nor an external reference"
+    )]
+    Reference { part: usize, value: T },
+}
+
+/// Decorator that rejects queries and replacements the inner storage could
+/// misread.
+///
+/// Every value is checked against [`LinksConstants`] before it reaches the
+/// inner storage:
+///
+/// - an index must be [internal](LinksConstants::is_internal), or `any` in a
+///   query;
+/// - a source or a target must be `null` or a
+///   [reference](LinksConstants::is_reference), or `any` in a query;
+/// - [reserved](LinksConstants::is_reserved) constants such as `skip` or
+///   `error` are refused everywhere else.
+///
+/// External references are accepted only when `external_range` is configured,
+/// and never as an index.
+/// [`count_links`](Links::count_links) cannot fail, so it counts no links for
+/// an invalid query.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{Error, LinksConstants, LinksExt, MemoryLinks, ValidatedLinks, ValidationError};
+///
+/// let constants = LinksConstants::via_ranges(1..=1000, None);
+/// let mut links = ValidatedLinks::new(MemoryLinks::<u32>::with_constants(constants.clone()));
+/// let a = links.create_link(1, 1).unwrap();
+///
+/// assert!(matches!(
+///     links.update(a, 2000, a),
+///     Err(Error::Invalid(ValidationError::Reference { part: 1, value: 2000 }))
+/// ));
+/// assert!(matches!(
+///     links.update(a, a, constants.skip),
+///     Err(Error::Invalid(ValidationError::Reserved { part: 2, .. }))
+/// ));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ValidatedLinks<T: LinkType, L> {
+    links: L,
+    constants: LinksConstants<T>,
+}
+
+impl<T: LinkType, L: Links<T>> ValidatedLinks<T, L> {
+    pub fn new(links: L) -> Self {
+        Self {
+            constants: links.constants_links(),
+            links,
+        }
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.links
+    }
+
+    pub fn into_inner(self) -> L {
+        self.links
+    }
+
+    fn value(&self, part: usize, value: T, wildcard: bool) -> Result<(), ValidationError<T>> {
+        let constants = &self.constants;
+        if wildcard && value == constants.any {
+            return Ok(());
+        }
+        if constants.is_reserved(value) {
+            return Err(ValidationError::Reserved { part, value });
+        }
+        if part == constants.index_part.as_usize() {
+            return if constants.is_internal(value) {
+                Ok(())
+            } else if constants.is_external(value) {
+                Err(ValidationError::External { part, value })
+            } else {
+                Err(ValidationError::Address { part, value })
+            };
+        }
+        if value == constants.null || constants.is_reference(value) {
+            Ok(())
+        } else {
+            Err(ValidationError::Reference { part, value })
+        }
+    }
+
+    fn check(&self, values: &[T], wildcard: bool) -> Result<(), ValidationError<T>> {
+        let parts = self.constants.target_part.as_usize() + 1;
+        if values.len() > parts || (!wildcard && values.len() < parts) {
+            return Err(ValidationError::Length(values.len()));
+        }
+        values
+            .iter()
+            .enumerate()
+            .try_for_each(|(part, &value)| self.value(part, value, wildcard))
+    }
+
+    fn query(&self, query: &[T]) -> Result<(), ValidationError<T>> {
+        self.check(query, true)
+    }
+
+    fn replacement(&self, replacement: &[T]) -> Result<(), ValidationError<T>> {
+        self.check(replacement, false)
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for ValidatedLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        match self.query(query) {
+            Ok(()) => self.links.count_links(query),
+            Err(_) => T::funty(0),
+        }
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.query(query)?;
+        self.links.create_links(query, handler)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        self.query(query)?;
+        self.links.each_links(query, handler)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.query(query)?;
+        self.replacement(replacement)?;
+        self.links.update_links(query, replacement, handler)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        self.query(query)?;
+        self.links.delete_links(query, handler)
+    }
+}
@@ -0,0 +1,454 @@
+use std::{
+    collections::BTreeMap,
+    convert::TryInto,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    mem,
+    path::Path,
+};
+
+use crate::{
+    Change, Error, FileLinks, Flow, Link, LinkType, Links, LinksConstants, LinksExt, ReadHandler,
+    WriteHandler,
+};
+
+const MAGIC: &[u8; 8] = b"LINKSWAL";
+const VERSION: u8 = 1;
+/// Magic, version, size of a link part, and reserved bytes.
+const HEADER: u64 = 16;
+
+const CHANGE: u8 = 1;
+const COMMIT: u8 = 2;
+const ABORT: u8 = 3;
+
+/// When [`WalLinks`] forces the log to disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SyncPolicy {
+    /// Before every mutation is applied and after it completes, so that a
+    /// mutation that returned is never lost.
+    Always,
+    /// Before every `n`th mutation is applied. A crash may lose up to `n`
+    /// logged mutations, together with their effect on a storage that did
+    /// not write them back either.
+    Every(usize),
+    /// Never. The operating system writes the log back on its own schedule,
+    /// and [`WalLinks::sync`] or [`WalLinks::checkpoint`] force it.
+    Never,
+}
+
+/// Failure of [`WalLinks`], reported as [`Error::Wal`].
+#[derive(thiserror::Error, Debug)]
+pub enum WalError {
+    #[error("write-ahead log is corrupted at byte {0}")]
+    Corrupted(u64),
+
+    #[error("write-ahead log I/O failed: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Storage that can write its contents back to durable media.
+pub trait Durable {
+    fn sync(&mut self) -> io::Result<()>;
+}
+
+impl<T: LinkType> Durable for FileLinks<T> {
+    fn sync(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+/// Outcome of [`WalLinks::recover`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Recovery {
+    /// Mutations found complete in the log.
+    pub committed: usize,
+    /// Mutations that failed or were interrupted, and were undone.
+    pub discarded: usize,
+    /// Links that were rewritten to match the log.
+    pub repaired: usize,
+}
+
+/// Decorator that appends every mutation to a write-ahead log before applying
+/// it to the inner storage.
+///
+/// Each mutation is logged as the [`Change`] its handler will receive, followed
+/// by a commit or abort record once the inner storage returns. Records carry a
+/// CRC-32 checksum. After a crash, [`recover`](Self::recover) brings the storage
+/// back to the state left by the committed mutations: it redoes what the
+/// storage lost and undoes what was interrupted.
+///
+/// New links get addresses chosen by the decorator, so that they can be logged
+/// in advance, and the inner storage has to support creating links at a chosen
+/// address. Decorators that turn one mutation into several belong outside
+/// `WalLinks`, so that each of those mutations is logged on its own.
+///
+/// [`checkpoint`](Self::checkpoint) writes a [`Durable`] storage back and then
+/// empties the log. A storage that is not durable, such as
+/// [`MemoryLinks`](crate::MemoryLinks), can instead be rebuilt by recovering
+/// the whole log into an empty storage.
+///
+/// # Examples
+///
+/// ```
+/// use platform_data::{Link, LinksExt, MemoryLinks, SyncPolicy, WalLinks};
+/// # let dir = tempfile::tempdir().unwrap();
+/// # let path = dir.path().join("links.wal");
+///
+/// let mut links = WalLinks::open(MemoryLinks::<u64>::new(), &path, SyncPolicy::Always).unwrap();
+/// let a = links.create_link(1, 1).unwrap();
+/// drop(links);
+///
+/// let mut links = WalLinks::open(MemoryLinks::<u64>::new(), &path, SyncPolicy::Always).unwrap();
+/// links.recover().unwrap();
+/// assert_eq!(links.get_link(a), Some(Link::new(a, 1, 1)));
+/// ```
+#[derive(Debug)]
+pub struct WalLinks<T: LinkType, L> {
+    links: L,
+    constants: LinksConstants<T>,
+    file: File,
+    policy: SyncPolicy,
+    unsynced: usize,
+    hint: T,
+    buf: Vec<u8>,
+}
+
+impl<T: LinkType, L: Links<T>> WalLinks<T, L> {
+    /// Opens or creates the log at `path` for `links`.
+    ///
+    /// Records already in the log are left alone until [`recover`](Self::recover).
+    pub fn open<P: AsRef<Path>>(
+        links: L,
+        path: P,
+        policy: SyncPolicy,
+    ) -> Result<Self, Error<'static, T>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(WalError::Io)?;
+
+        let mut header = [0; HEADER as usize];
+        header[..8].copy_from_slice(MAGIC);
+        header[8] = VERSION;
+        header[9] = mem::size_of::<T>() as u8;
+
+        let len = file.metadata().map_err(WalError::Io)?.len();
+        if len == 0 {
+            file.write_all(&header).map_err(WalError::Io)?;
+            file.sync_all().map_err(WalError::Io)?;
+        } else {
+            let mut found = [0; HEADER as usize];
+            file.seek(SeekFrom::Start(0)).map_err(WalError::Io)?;
+            file.read_exact(&mut found)
+                .map_err(|_| WalError::Corrupted(0))?;
+            if found != header {
+                return Err(WalError::Corrupted(0).into());
+            }
+        }
+
+        let constants = links.constants_links();
+        Ok(Self {
+            links,
+            hint: *constants.internal_range.start(),
+            constants,
+            file,
+            policy,
+            unsynced: 0,
+            buf: Vec::new(),
+        })
+    }
+
+    pub fn get_ref(&self) -> &L {
+        &self.links
+    }
+
+    pub fn into_inner(self) -> L {
+        self.links
+    }
+
+    /// Returns the size of the log in bytes.
+    pub fn log_len(&self) -> Result<u64, Error<'static, T>> {
+        Ok(self.file.metadata().map_err(WalError::Io)?.len())
+    }
+
+    /// Forces the log to disk.
+    pub fn sync(&mut self) -> Result<(), Error<'static, T>> {
+        self.unsynced = 0;
+        Ok(self.file.sync_data().map_err(WalError::Io)?)
+    }
+
+    /// Drops the records from byte `len` on, such as a corrupted tail reported
+    /// by [`recover`](Self::recover).
+    pub fn truncate(&mut self, len: u64) -> Result<(), Error<'static, T>> {
+        self.file.set_len(len.max(HEADER)).map_err(WalError::Io)?;
+        self.sync()
+    }
+
+    /// Brings the storage to the state left by the committed mutations in the log.
+    ///
+    /// The whole log is read before the storage is touched, so a corrupted
+    /// record is reported as [`WalError::Corrupted`] with its offset and
+    /// leaves the storage unchanged.
+    pub fn recover(&mut self) -> Result<Recovery, Error<'_, T>> {
+        let mut bytes = Vec::new();
+        self.file
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| self.file.read_to_end(&mut bytes))
+            .map_err(WalError::Io)?;
+
+        let mut recovery = Recovery::default();
+        // Target state and position of the last change of every logged link.
+        let mut target = BTreeMap::new();
+        let mut pending = Vec::new();
+        let mut sequence = 0;
+        let mut offset = HEADER as usize;
+        while offset < bytes.len() {
+            let (kind, change, len) = self
+                .decode(&bytes[offset..])
+                .ok_or(WalError::Corrupted(offset as u64))?;
+            offset += len;
+            match kind {
+                CHANGE => pending.push(change),
+                COMMIT => {
+                    recovery.committed += 1;
+                    for change in pending.drain(..) {
+                        sequence += 1;
+                        target.insert(index_of(&change), (change.after, sequence));
+                    }
+                }
+                _ => {
+                    recovery.discarded += 1;
+                    for change in pending.drain(..) {
+                        target
+                            .entry(index_of(&change))
+                            .or_insert((change.before, 0));
+                    }
+                }
+            }
+        }
+        if !pending.is_empty() {
+            recovery.discarded += 1;
+            for change in pending.drain(..) {
+                target
+                    .entry(index_of(&change))
+                    .or_insert((change.before, 0));
+            }
+            self.append(ABORT, None)?;
+            self.sync()?;
+        }
+
+        let mut deletions = Vec::new();
+        for (&index, &(link, sequence)) in &target {
+            let found = self.links.get_link(index);
+            if link.is_nothing() {
+                if found.is_some() {
+                    deletions.push((sequence, index));
+                }
+            } else if found != Some(link) {
+                Change::new(found.unwrap_or_else(Link::nothing), link)
+                    .apply(&mut self.links, &mut |_, _| Flow::Continue)
+                    .map_err(Error::into_owned)?;
+                recovery.repaired += 1;
+            }
+        }
+        deletions.sort_unstable();
+        for (_, index) in deletions {
+            self.links.delete(index).map_err(Error::into_owned)?;
+            recovery.repaired += 1;
+        }
+        Ok(recovery)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<(u8, Change<T>, usize)> {
+        let len = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        let checksum = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?);
+        let payload = bytes.get(8..8 + len)?;
+        if crc32fast::hash(payload) != checksum {
+            return None;
+        }
+
+        let size = mem::size_of::<T>();
+        let part = |n: usize| {
+            let mut value = [0; 16];
+            value[..size].copy_from_slice(&payload[1 + n * size..1 + (n + 1) * size]);
+            T::try_from(u128::from_le_bytes(value)).ok()
+        };
+        let change = match (payload.first()?, len) {
+            (&CHANGE, len) if len == 1 + 6 * size => Change::new(
+                Link::new(part(0)?, part(1)?, part(2)?),
+                Link::new(part(3)?, part(4)?, part(5)?),
+            ),
+            (&(COMMIT | ABORT), 1) => Change::default(),
+            _ => return None,
+        };
+        Some((payload[0], change, 8 + len))
+    }
+
+    fn append(&mut self, kind: u8, change: Option<&Change<T>>) -> Result<(), Error<'static, T>> {
+        let size = mem::size_of::<T>();
+        self.buf.clear();
+        self.buf.extend_from_slice(&[0; 8]);
+        self.buf.push(kind);
+        if let Some(change) = change {
+            for link in [change.before, change.after].iter() {
+                for &part in [link.index, link.source, link.target].iter() {
+                    let value: u128 = part.try_into().unwrap();
+                    self.buf.extend_from_slice(&value.to_le_bytes()[..size]);
+                }
+            }
+        }
+        let len = (self.buf.len() - 8) as u32;
+        let checksum = crc32fast::hash(&self.buf[8..]);
+        self.buf[..4].copy_from_slice(&len.to_le_bytes());
+        self.buf[4..8].copy_from_slice(&checksum.to_le_bytes());
+        Ok(self.file.write_all(&self.buf).map_err(WalError::Io)?)
+    }
+
+    /// Logs `change` before it is applied.
+    fn begin(&mut self, change: &Change<T>) -> Result<(), Error<'static, T>> {
+        self.append(CHANGE, Some(change))?;
+        self.unsynced += 1;
+        match self.policy {
+            SyncPolicy::Always => self.sync(),
+            SyncPolicy::Every(n) if self.unsynced >= n => self.sync(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Logs whether the change logged by [`begin`](Self::begin) was applied.
+    fn end(&mut self, applied: bool) -> Result<(), Error<'static, T>> {
+        self.append(if applied { COMMIT } else { ABORT }, None)?;
+        match self.policy {
+            SyncPolicy::Always => self.sync(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns a free address for a new link, searching from the last one given.
+    fn free_address(&mut self) -> Result<T, Error<'static, T>> {
+        let (start, end) = (
+            *self.constants.internal_range.start(),
+            *self.constants.internal_range.end(),
+        );
+        let first = self.hint.max(start).min(end);
+        let mut index = first;
+        while self.links.exists(index) {
+            index = if index == end {
+                start
+            } else {
+                index + T::funty(1)
+            };
+            if index == first {
+                return Err(Error::LimitReached(end));
+            }
+        }
+        self.hint = index;
+        Ok(index)
+    }
+
+    fn query_index(&self, query: &[T]) -> T {
+        query
+            .get(self.constants.index_part.as_usize())
+            .copied()
+            .unwrap_or(self.constants.any)
+    }
+
+    /// Logs `change`, applies it with `apply` and logs the outcome.
+    fn mutate(
+        &mut self,
+        change: Change<T>,
+        apply: impl FnOnce(&mut L) -> Result<Flow, Error<'_, T>>,
+    ) -> Result<Flow, Error<'static, T>> {
+        self.begin(&change)?;
+        let result = apply(&mut self.links).map_err(Error::into_owned);
+        self.end(result.is_ok())?;
+        result
+    }
+}
+
+fn index_of<T: LinkType>(change: &Change<T>) -> T {
+    if change.before.is_nothing() {
+        change.after.index
+    } else {
+        change.before.index
+    }
+}
+
+impl<T: LinkType, L: Links<T> + Durable> WalLinks<T, L> {
+    /// Writes the storage back and empties the log.
+    pub fn checkpoint(&mut self) -> Result<(), Error<'static, T>> {
+        self.links.sync().map_err(WalError::Io)?;
+        self.truncate(HEADER)
+    }
+}
+
+impl<T: LinkType, L: Links<T>> Links<T> for WalLinks<T, L> {
+    fn constants_links(&self) -> LinksConstants<T> {
+        self.constants.clone()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.links.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let requested = self.query_index(query);
+        let index = if requested == self.constants.any {
+            self.free_address()?
+        } else if self.links.exists(requested) {
+            return self.links.create_links(query, handler);
+        } else {
+            requested
+        };
+
+        let null = self.constants.null;
+        let change = Change::new(Link::nothing(), Link::new(index, null, null));
+        self.mutate(change, |links| links.create_links(&[index], handler))
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Result<Flow, Error<'_, T>> {
+        self.links.each_links(query, handler)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        replacement: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = self.query_index(query);
+        let before = match self.links.get_link(index) {
+            Some(before) => before,
+            None => return self.links.update_links(query, replacement, handler),
+        };
+        let after = Link::new(
+            index,
+            replacement[self.constants.source_part.as_usize()],
+            replacement[self.constants.target_part.as_usize()],
+        );
+        self.mutate(Change::new(before, after), |links| {
+            links.update_links(query, replacement, handler)
+        })
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<'_, T>> {
+        let index = self.query_index(query);
+        let before = match self.links.get_link(index) {
+            Some(before) => before,
+            None => return self.links.delete_links(query, handler),
+        };
+        self.mutate(Change::new(before, Link::nothing()), |links| {
+            links.delete_links(query, handler)
+        })
+    }
+}
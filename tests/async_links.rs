@@ -0,0 +1,238 @@
+#![cfg(feature = "tokio")]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use platform_data::{
+    AsyncLinks, BlockingLinks, Error, Flow, Links, LinksConstants, MemoryLinks, ReadHandler,
+    SpawnedLinks, WriteHandler,
+};
+use tokio::runtime::Runtime;
+
+/// Counts the links the storage visits, whatever its handler answers.
+struct Visits {
+    links: MemoryLinks<u64>,
+    visited: Arc<AtomicUsize>,
+}
+
+impl Links<u64> for Visits {
+    fn constants_links(&self) -> LinksConstants<u64> {
+        self.links.constants_links()
+    }
+
+    fn count_links(&self, query: &[u64]) -> u64 {
+        self.links.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[u64],
+        handler: WriteHandler<'_, u64>,
+    ) -> Result<Flow, Error<'_, u64>> {
+        self.links.create_links(query, handler)
+    }
+
+    fn each_links(
+        &self,
+        query: &[u64],
+        handler: ReadHandler<'_, u64>,
+    ) -> Result<Flow, Error<'_, u64>> {
+        self.links.each_links(query, &mut |link| {
+            self.visited.fetch_add(1, Ordering::SeqCst);
+            handler(link)
+        })
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[u64],
+        replacement: &[u64],
+        handler: WriteHandler<'_, u64>,
+    ) -> Result<Flow, Error<'_, u64>> {
+        self.links.update_links(query, replacement, handler)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[u64],
+        handler: WriteHandler<'_, u64>,
+    ) -> Result<Flow, Error<'_, u64>> {
+        self.links.delete_links(query, handler)
+    }
+}
+
+fn visits(count: u64) -> (Visits, Arc<AtomicUsize>) {
+    use platform_data::LinksExt;
+
+    let mut links = MemoryLinks::new();
+    for target in 100..100 + count {
+        links.create_link(100, target).unwrap();
+    }
+    let visited = Arc::new(AtomicUsize::new(0));
+    let visits = Visits {
+        links,
+        visited: Arc::clone(&visited),
+    };
+    (visits, visited)
+}
+
+fn blocking(runtime: &Runtime) -> BlockingLinks<u64, SpawnedLinks<u64, MemoryLinks<u64>>> {
+    let links = runtime.block_on(async { SpawnedLinks::new(MemoryLinks::new()) });
+    BlockingLinks::new(links, runtime.handle().clone())
+}
+
+async fn create(links: &mut SpawnedLinks<u64, MemoryLinks<u64>>, source: u64, target: u64) -> u64 {
+    let mut index = 0;
+    links
+        .create_links(&[], |_, after| {
+            index = after[0];
+            async { Flow::Continue }
+        })
+        .await
+        .unwrap();
+    links
+        .update_links(&[index], &[index, source, target], |_, _| async {
+            Flow::Continue
+        })
+        .await
+        .unwrap();
+    index
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms_through_both_adapters() {
+    use platform_data::testing;
+
+    let runtime = Runtime::new().unwrap();
+    testing::run(|| blocking(&runtime));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handlers_await_and_break() {
+    let mut links = SpawnedLinks::new(MemoryLinks::<u64>::new());
+    for target in 100..105 {
+        create(&mut links, 100, target).await;
+    }
+    assert_eq!(links.count_links(&[]).await, 5);
+
+    let mut seen = vec![];
+    let flow = links
+        .each_links(&[], |link| {
+            seen.push(link[2]);
+            let stop = seen.len() == 3;
+            async move {
+                tokio::task::yield_now().await;
+                if stop {
+                    Flow::Break
+                } else {
+                    Flow::Continue
+                }
+            }
+        })
+        .await
+        .unwrap();
+    assert_eq!(flow, Flow::Break);
+    assert_eq!(seen, [100, 101, 102]);
+
+    let mut deleted = vec![];
+    links
+        .delete_links(&[3], |before, after| {
+            deleted.push((before.to_vec(), after.to_vec()));
+            async { Flow::Continue }
+        })
+        .await
+        .unwrap();
+    assert_eq!(deleted, [(vec![3, 100, 102], vec![])]);
+    assert_eq!(
+        links
+            .into_inner()
+            .map(|links| Links::count_links(&links, &[])),
+        Some(4)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_break_stops_the_storage() {
+    let (storage, visited) = visits(5);
+    let links = SpawnedLinks::new(storage);
+    let flow = links
+        .each_links(&[], |_| async { Flow::Break })
+        .await
+        .unwrap();
+    assert_eq!(flow, Flow::Break);
+    assert_eq!(visited.load(Ordering::SeqCst), 1);
+
+    let (storage, visited) = visits(5);
+    let handle = tokio::runtime::Handle::current();
+    let flow = tokio::task::spawn_blocking(move || {
+        let links = BlockingLinks::new(SpawnedLinks::new(storage), handle);
+        links.each_links(&[], &mut |_| Flow::Break).unwrap()
+    })
+    .await
+    .unwrap();
+    assert_eq!(flow, Flow::Break);
+    assert_eq!(visited.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn errors_are_returned() {
+    let mut links = SpawnedLinks::new(MemoryLinks::<u64>::new());
+    let error = links
+        .delete_links(&[500], |_, _| async { Flow::Continue })
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotExists(500)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn futures_can_be_spawned() {
+    let task = tokio::spawn(async {
+        let mut links = SpawnedLinks::new(MemoryLinks::<u64>::new());
+        let a = create(&mut links, 100, 200).await;
+        let any = links.constants_links().any;
+        let mut found = vec![];
+        links
+            .each_links(&[any, 100, 200], |link| {
+                found.push(link[0]);
+                async { Flow::Continue }
+            })
+            .await
+            .unwrap();
+        (a, found)
+    });
+    let (a, found) = task.await.unwrap();
+    assert_eq!(found, [a]);
+}
+
+// Compiles only because every `AsyncLinks` future is `Send`.
+async fn count_on_another_task<A: AsyncLinks<u64> + 'static>(links: A) -> u64 {
+    tokio::spawn(async move { links.count_links(&[]).await })
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn generic_futures_can_be_spawned() {
+    let mut links = SpawnedLinks::new(MemoryLinks::<u64>::new());
+    create(&mut links, 100, 200).await;
+    assert_eq!(count_on_another_task(links).await, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_links_run_off_the_runtime() {
+    let handle = tokio::runtime::Handle::current();
+    let storage = SpawnedLinks::new(MemoryLinks::<u64>::new());
+    let count = tokio::task::spawn_blocking(move || {
+        use platform_data::LinksExt;
+
+        let mut links = BlockingLinks::new(storage, handle);
+        links.create_link(100, 200).unwrap();
+        links.count_all()
+    })
+    .await
+    .unwrap();
+    assert_eq!(count, 1);
+}
@@ -1,4 +1,4 @@
-use platform_data::LinksConstants;
+use platform_data::{ConstantsError, Hybrid, LinksConstants};
 
 #[test]
 fn test_new_creates_internal_constants() {
@@ -193,3 +193,163 @@ fn test_default_internal_external_ranges() {
     // Internal range should be limited
     assert!(*constants.internal_range.end() < u64::MAX);
 }
+
+const ANY: u64 = LinksConstants::<u64>::INTERNAL.any;
+const SKIP: u64 = LinksConstants::<u64>::INTERNAL.skip;
+static EXTERNAL: LinksConstants<u32> = LinksConstants::<u32>::EXTERNAL;
+
+#[test]
+fn test_const_constants_match_runtime_ones() {
+    assert_eq!(LinksConstants::<u8>::INTERNAL, LinksConstants::internal());
+    assert_eq!(LinksConstants::<u16>::INTERNAL, LinksConstants::internal());
+    assert_eq!(LinksConstants::<u64>::EXTERNAL, LinksConstants::external());
+    assert_eq!(LinksConstants::<u128>::EXTERNAL, LinksConstants::external());
+    assert_eq!(LinksConstants::<usize>::INTERNAL, LinksConstants::new());
+    assert_eq!(EXTERNAL, LinksConstants::external());
+
+    const CUSTOM: LinksConstants<u32> = LinksConstants::<u32>::full_new_const(4, 1..=1000, None);
+    assert_eq!(CUSTOM, LinksConstants::full_new(4, 1..=1000, None));
+}
+
+#[test]
+fn test_match_on_const_constants() {
+    let describe = |value: u64| match value {
+        0 => "null",
+        ANY => "any",
+        SKIP => "skip",
+        _ => "address",
+    };
+    let constants = LinksConstants::<u64>::new();
+    assert_eq!(describe(constants.any), "any");
+    assert_eq!(describe(constants.skip), "skip");
+    assert_eq!(describe(42), "address");
+}
+
+#[test]
+fn test_builder_defaults_match_new() {
+    assert_eq!(
+        LinksConstants::<u64>::builder().build(),
+        Ok(LinksConstants::new())
+    );
+    assert_eq!(
+        LinksConstants::<u8>::builder()
+            .internal_range(1..=200)
+            .build(),
+        Ok(LinksConstants::via_ranges(1..=200, None))
+    );
+    assert_eq!(
+        LinksConstants::<u32>::builder()
+            .internal_range(1..=Hybrid::<u32>::HALF - 1)
+            .external_range(Hybrid::<u32>::HALF..=u32::MAX)
+            .build(),
+        Ok(LinksConstants::via_ranges(
+            1..=Hybrid::<u32>::HALF - 1,
+            Some(Hybrid::<u32>::HALF..=u32::MAX)
+        ))
+    );
+}
+
+#[test]
+fn test_builder_explicit_layout() {
+    let constants = LinksConstants::<u16>::builder()
+        .index_part(0)
+        .source_part(1)
+        .target_part(3)
+        .r#continue(1)
+        .r#break(2)
+        .skip(3)
+        .any(4)
+        .itself(5)
+        .error(6)
+        .internal_range(7..=1000)
+        .build()
+        .unwrap();
+    assert_eq!(
+        (
+            constants.index_part,
+            constants.source_part,
+            constants.target_part
+        ),
+        (0, 1, 3)
+    );
+    assert_eq!(constants.null, 0);
+    assert_eq!(constants.any, 4);
+    assert_eq!(constants.internal_range, 7..=1000);
+    assert!(constants.is_reserved(6));
+    assert!(!constants.is_reserved(7));
+}
+
+#[test]
+fn test_builder_rejects_inconsistent_layouts() {
+    let builder = || LinksConstants::<u8>::builder();
+    assert_eq!(
+        builder().target_part(1).build(),
+        Err(ConstantsError::TargetPart(1))
+    );
+    assert_eq!(
+        builder()
+            .index_part(2)
+            .source_part(0)
+            .target_part(3)
+            .build(),
+        Err(ConstantsError::PartLayout {
+            index: 2,
+            source: 0
+        })
+    );
+    assert_eq!(
+        builder().source_part(2).build(),
+        Err(ConstantsError::PartLayout {
+            index: 0,
+            source: 2
+        })
+    );
+    assert_eq!(
+        builder().null(u8::MAX).build(),
+        Err(ConstantsError::NonZeroNull(u8::MAX))
+    );
+    let (start, end) = (10, 5);
+    assert_eq!(
+        builder().internal_range(start..=end).build(),
+        Err(ConstantsError::EmptyRange { start, end })
+    );
+    assert_eq!(
+        builder().internal_range(1..=6).build(),
+        Err(ConstantsError::TooSmall { start: 1, end: 6 })
+    );
+    builder().internal_range(1..=7).build().unwrap();
+    assert_eq!(
+        builder().internal_range(0..=0).any(1).build(),
+        Err(ConstantsError::TooSmall { start: 0, end: 0 })
+    );
+    assert_eq!(
+        builder()
+            .internal_range(1..=100)
+            .external_range(90..=200)
+            .build(),
+        Err(ConstantsError::Overlap {
+            internal: 1..=94,
+            external: 90..=200
+        })
+    );
+    assert_eq!(
+        builder().internal_range(0..=100).build(),
+        Err(ConstantsError::NullInRange(0))
+    );
+    assert_eq!(
+        builder().any(10).build(),
+        Err(ConstantsError::ReservedInRange(10))
+    );
+    assert_eq!(
+        builder().any(0).build(),
+        Err(ConstantsError::DuplicateReserved(0))
+    );
+    assert_eq!(
+        builder().skip(u8::MAX).build(),
+        Err(ConstantsError::DuplicateReserved(u8::MAX))
+    );
+    assert_eq!(
+        ConstantsError::<u8>::TooSmall { start: 1, end: 6 }.to_string(),
+        "internal range 1..=6 is too small to hold the reserved values and an address"
+    );
+}
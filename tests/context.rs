@@ -0,0 +1,91 @@
+use platform_data::{
+    DeleteMode, DuplicatePolicy, Error, ErrorKind, Flow, Links, LinksError, LinksExt, MemoryLinks,
+    Operation, UniqueLinks, UsageLinks, ValidationError,
+};
+
+fn owned<V>(result: Result<V, Error<'_, u64>>) -> LinksError<u64> {
+    match result {
+        Err(error) => LinksError::from(error),
+        Ok(_) => panic!("succeeded"),
+    }
+}
+
+#[test]
+fn kinds_match_variants() {
+    let mut links = UsageLinks::new(MemoryLinks::<u64>::new(), DeleteMode::Restrict);
+    let a = links.create_link(100, 100).unwrap();
+    links.create_link(a, a).unwrap();
+
+    assert_eq!(owned(links.delete(a)).kind(), ErrorKind::HasUsages);
+    assert_eq!(owned(links.delete(500)).kind(), ErrorKind::NotExists);
+    assert_eq!(
+        Error::<u64>::from(ValidationError::Length(5)).kind(),
+        ErrorKind::Invalid
+    );
+    assert_eq!(
+        Error::<u64>::LimitReached(5).kind(),
+        ErrorKind::LimitReached
+    );
+}
+
+#[test]
+fn context_records_operation_query_and_found_link() {
+    let mut links = UniqueLinks::new(MemoryLinks::<u64>::new(), DuplicatePolicy::Reject);
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create().unwrap();
+
+    let query = [b];
+    let replacement = [b, 100, 200];
+    let error = links
+        .update_links(&query, &replacement, &mut |_, _| Flow::Continue)
+        .map_err(|error| error.context(Operation::Update, &query))
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(error.operation(), Some(Operation::Update));
+    assert_eq!(error.query(), [b]);
+    assert_eq!(error.found(), Some(a));
+    assert!(matches!(error.get_ref(), Error::AlreadyExists(index) if **index == a));
+    assert_eq!(error.to_string(), "update [2] failed");
+}
+
+#[test]
+fn without_context() {
+    let mut links = MemoryLinks::<u64>::new();
+    let error = owned(links.delete(500));
+    assert_eq!(error.operation(), None);
+    assert!(error.query().is_empty());
+    assert_eq!(error.found(), None);
+    assert_eq!(error.to_string(), "links operation failed");
+    assert_eq!(error.with_found(7).found(), Some(7));
+}
+
+#[test]
+fn source_is_the_wrapped_error() {
+    use std::error::Error as _;
+
+    let mut links = MemoryLinks::<u64>::new();
+    let error = owned(links.delete(500));
+    let source = error.source().unwrap();
+    assert_eq!(source.to_string(), "link 500 does not exist.");
+    assert!(matches!(
+        source.downcast_ref::<Error<'static, u64>>(),
+        Some(Error::NotExists(500))
+    ));
+}
+
+#[test]
+fn outlives_the_storage() {
+    fn fails() -> anyhow::Result<()> {
+        let mut links = MemoryLinks::<u64>::new();
+        links
+            .delete(500)
+            .map_err(|error| error.context(Operation::Delete, &[500]))?;
+        Ok(())
+    }
+
+    let error = fails().unwrap_err();
+    let error = error.downcast_ref::<LinksError<u64>>().unwrap();
+    assert_eq!(error.kind(), ErrorKind::NotExists);
+    assert!(matches!(error.get_ref(), Error::NotExists(500)));
+}
@@ -0,0 +1,198 @@
+use platform_data::{
+    Error, Flow, FlowWith, Link, Links, LinksConstants, LinksExt, MemoryLinks, ReadHandler,
+    WriteHandler,
+};
+
+/// Storage that ignores a `Flow::Break` from its handlers.
+struct Stubborn(MemoryLinks<usize>);
+
+impl Links<usize> for Stubborn {
+    fn constants_links(&self) -> LinksConstants<usize> {
+        self.0.constants_links()
+    }
+
+    fn count_links(&self, query: &[usize]) -> usize {
+        self.0.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[usize],
+        handler: WriteHandler<'_, usize>,
+    ) -> Result<Flow, Error<'_, usize>> {
+        self.0.create_links(query, &mut |before, after| {
+            handler(before, after);
+            Flow::Continue
+        })
+    }
+
+    fn each_links(
+        &self,
+        query: &[usize],
+        handler: ReadHandler<'_, usize>,
+    ) -> Result<Flow, Error<'_, usize>> {
+        self.0.each_links(query, &mut |link| {
+            handler(link);
+            Flow::Continue
+        })
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[usize],
+        replacement: &[usize],
+        handler: WriteHandler<'_, usize>,
+    ) -> Result<Flow, Error<'_, usize>> {
+        self.0
+            .update_links(query, replacement, &mut |before, after| {
+                handler(before, after);
+                Flow::Continue
+            })
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[usize],
+        handler: WriteHandler<'_, usize>,
+    ) -> Result<Flow, Error<'_, usize>> {
+        self.0.delete_links(query, &mut |before, after| {
+            handler(before, after);
+            Flow::Continue
+        })
+    }
+}
+
+#[test]
+fn create_and_get() {
+    let mut links = MemoryLinks::<usize>::new();
+    let index = links.create().unwrap();
+    assert_eq!(links.get_link(index), Some(Link::new(index, 0, 0)));
+    assert_eq!(links.get_link(index + 1), None);
+}
+
+#[test]
+fn create_link_sets_source_and_target() {
+    let mut links = MemoryLinks::<usize>::new();
+    let a = links.create_link(1, 1).unwrap();
+    let b = links.create_link(a, 1).unwrap();
+    assert_eq!(links.get_link(b), Some(Link::new(b, a, 1)));
+    assert_eq!(links.count_all(), 2);
+}
+
+#[test]
+fn exists() {
+    let mut links = MemoryLinks::<usize>::new();
+    let index = links.create().unwrap();
+    assert!(links.exists(index));
+    let constants = links.constants_links();
+    let outside = *constants.internal_range.end() + 1;
+    for missing in [constants.any, outside] {
+        assert!(!links.exists(missing));
+        assert_eq!(links.get_link(missing), None);
+    }
+    assert_eq!(links.delete(index).unwrap(), index);
+    assert!(!links.exists(index));
+}
+
+#[test]
+fn update() {
+    let mut links = MemoryLinks::<usize>::new();
+    let index = links.create().unwrap();
+    assert_eq!(links.update(index, index, index).unwrap(), index);
+    assert_eq!(links.get_link(index), Some(Link::new(index, index, index)));
+    assert!(matches!(
+        links.update(index + 1, 1, 1),
+        Err(Error::NotExists(missing)) if missing == index + 1
+    ));
+}
+
+#[test]
+fn delete_missing() {
+    let mut links = MemoryLinks::<usize>::new();
+    assert!(matches!(links.delete(1), Err(Error::NotExists(1))));
+}
+
+#[test]
+fn search() {
+    let mut links = MemoryLinks::<usize>::new();
+    let a = links.create_link(1, 1).unwrap();
+    let b = links.create().unwrap();
+    links.update(b, b, a).unwrap();
+    assert_eq!(links.search(a, a), Some(a));
+    assert_eq!(links.search(b, a), Some(b));
+    assert_eq!(links.search(a, b), None);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn works_through_trait_objects_and_other_stores() {
+    use platform_data::FileLinks;
+    use tempfile::NamedTempFile;
+
+    let file = NamedTempFile::new().unwrap();
+    let mut links = FileLinks::<u32>::open(file.path()).unwrap();
+    let links: &mut dyn platform_data::Links<u32> = &mut links;
+    let a = links.create_link(1, 1).unwrap();
+    assert_eq!(links.search(1, 1), Some(a));
+    assert_eq!(links.count_all(), 1);
+}
+
+#[test]
+fn handlers_break_with_values() {
+    let mut links = MemoryLinks::<usize>::new();
+    let a = links.create_link(100, 100).unwrap();
+    let b = links.create_link(a, 200).unwrap();
+    let any = links.constants_links().any;
+
+    assert_eq!(
+        links
+            .each_with(&[any, a], |link| FlowWith::Break(Link::new(
+                link[0], link[1], link[2]
+            )))
+            .unwrap(),
+        FlowWith::Break(Link::new(b, a, 200))
+    );
+    assert_eq!(
+        links
+            .each_with(&[any, 300], |_| FlowWith::Break(()))
+            .unwrap(),
+        FlowWith::Continue
+    );
+
+    let created = links
+        .create_with(&[], |_, after| FlowWith::Break(after[0]))
+        .unwrap();
+    let c = created.break_value().unwrap();
+    assert!(links.exists(c));
+
+    let before = links
+        .update_with(&[c], &[c, a, b], |before, _| {
+            FlowWith::Break(before.to_vec())
+        })
+        .unwrap();
+    assert_eq!(before, FlowWith::Break(vec![c, 0, 0]));
+    assert_eq!(links.search(a, b), Some(c));
+
+    let deleted = links
+        .delete_with(&[c], |before, _| FlowWith::Break(before[2]))
+        .unwrap();
+    assert_eq!(deleted, FlowWith::Break(b));
+    assert_eq!(links.search(a, b), None);
+}
+
+#[test]
+fn handlers_are_not_called_after_they_break() {
+    let mut links = Stubborn(MemoryLinks::new());
+    for target in 100..105 {
+        links.create_link(100, target).unwrap();
+    }
+
+    let mut calls = 0;
+    let found = links
+        .each_with(&[], |link| {
+            calls += 1;
+            FlowWith::Break(link[2])
+        })
+        .unwrap();
+    assert_eq!((found, calls), (FlowWith::Break(100), 1));
+}
@@ -0,0 +1,218 @@
+#![cfg(feature = "std")]
+
+use std::{fs, io};
+
+use platform_data::{Error, FileLinks, Flow, Links, LinksConstants};
+use tempfile::NamedTempFile;
+
+fn create(links: &mut FileLinks<u64>) -> u64 {
+    let mut index = 0;
+    links
+        .create_links(&[], &mut |_, after| {
+            index = after[0];
+            Flow::Continue
+        })
+        .unwrap();
+    index
+}
+
+fn update(links: &mut FileLinks<u64>, index: u64, source: u64, target: u64) {
+    links
+        .update_links(&[index], &[index, source, target], &mut |_, _| {
+            Flow::Continue
+        })
+        .unwrap();
+}
+
+fn all(links: &FileLinks<u64>) -> Vec<Vec<u64>> {
+    let mut all = vec![];
+    links
+        .each_links(&[], &mut |link| {
+            all.push(link.to_vec());
+            Flow::Continue
+        })
+        .unwrap();
+    all
+}
+
+#[test]
+fn links_survive_reopen() {
+    let file = NamedTempFile::new().unwrap();
+    {
+        let mut links = FileLinks::open(file.path()).unwrap();
+        let a = create(&mut links);
+        let b = create(&mut links);
+        update(&mut links, a, a, a);
+        update(&mut links, b, a, b);
+        links.flush().unwrap();
+    }
+
+    let links = FileLinks::<u64>::open(file.path()).unwrap();
+    let any = links.constants_links().any;
+    assert_eq!(all(&links), [vec![1, 1, 1], vec![2, 1, 2]]);
+    assert_eq!(links.count_links(&[any, 1, any]), 2);
+    assert_eq!(links.count_links(&[any, any, 2]), 1);
+}
+
+#[test]
+fn file_grows_with_links() {
+    let file = NamedTempFile::new().unwrap();
+    let mut links = FileLinks::open(file.path()).unwrap();
+    let initial = fs::metadata(file.path()).unwrap().len();
+
+    let count = 3000;
+    for _ in 0..count {
+        create(&mut links);
+    }
+    for index in 1..=count {
+        update(&mut links, index, index % 10 + 1, index);
+    }
+
+    assert!(fs::metadata(file.path()).unwrap().len() > initial);
+    assert_eq!(links.count_links(&[]), count);
+    let any = links.constants_links().any;
+    assert_eq!(links.count_links(&[any, 1, any]), count / 10);
+    assert_eq!(links.count_links(&[any, any, 77]), 1);
+}
+
+#[test]
+fn deleted_addresses_are_reused_after_reopen() {
+    let file = NamedTempFile::new().unwrap();
+    {
+        let mut links = FileLinks::open(file.path()).unwrap();
+        for _ in 0..3 {
+            create(&mut links);
+        }
+        links
+            .delete_links(&[2], &mut |before, after| {
+                assert_eq!(before, [2, 0, 0]);
+                assert!(after.is_empty());
+                Flow::Continue
+            })
+            .unwrap();
+    }
+
+    let mut links = FileLinks::open(file.path()).unwrap();
+    assert_eq!(links.count_links(&[]), 2);
+    assert_eq!(links.count_links(&[2]), 0);
+    assert_eq!(create(&mut links), 2);
+    assert_eq!(create(&mut links), 4);
+}
+
+#[test]
+fn create_at_requested_address() {
+    let file = NamedTempFile::new().unwrap();
+    let mut links = FileLinks::<u64>::open(file.path()).unwrap();
+    links
+        .create_links(&[3], &mut |_, after| {
+            assert_eq!(after, [3, 0, 0]);
+            Flow::Continue
+        })
+        .unwrap();
+    assert!(matches!(
+        links.create_links(&[3], &mut |_, _| Flow::Continue),
+        Err(Error::AlreadyExists(index)) if *index == 3
+    ));
+
+    let mut created = [create(&mut links), create(&mut links), create(&mut links)];
+    created.sort();
+    assert_eq!(created, [1, 2, 4]);
+}
+
+#[test]
+fn far_requested_address_is_rejected() {
+    let file = NamedTempFile::new().unwrap();
+    let mut links = FileLinks::<u64>::open(file.path()).unwrap();
+    assert!(matches!(
+        links.create_links(&[1 << 40], &mut |_, _| Flow::Continue),
+        Err(Error::AllocFailed(error)) if error.kind() == io::ErrorKind::InvalidInput
+    ));
+    assert_eq!(links.count_links(&[]), 0);
+    assert_eq!(create(&mut links), 1);
+}
+
+#[test]
+fn errors_match_links_contract() {
+    let file = NamedTempFile::new().unwrap();
+    let mut links = FileLinks::open(file.path()).unwrap();
+    assert!(matches!(
+        links.delete_links(&[1], &mut |_, _| Flow::Continue),
+        Err(Error::NotExists(1))
+    ));
+
+    let a = create(&mut links);
+    let b = create(&mut links);
+    update(&mut links, b, a, a);
+    assert!(matches!(
+        links.delete_links(&[a], &mut |_, _| Flow::Continue),
+        Err(Error::HasUsages(_))
+    ));
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    let file = NamedTempFile::new().unwrap();
+    fs::write(file.path(), [0; 7]).unwrap();
+    assert!(matches!(
+        FileLinks::<u64>::open(file.path()),
+        Err(Error::AllocFailed(_))
+    ));
+
+    let dir = tempfile::tempdir().unwrap();
+    assert!(matches!(
+        FileLinks::<u64>::open(dir.path()),
+        Err(Error::AllocFailed(_))
+    ));
+
+    // 64 zero bytes are a whole record for either width, but carry no magic.
+    fs::write(file.path(), [0; 64]).unwrap();
+    assert!(matches!(
+        FileLinks::<u64>::open(file.path()),
+        Err(Error::AllocFailed(error)) if error.kind() == io::ErrorKind::InvalidData
+    ));
+
+    let file = NamedTempFile::new().unwrap();
+    let mut links = FileLinks::<u64>::open(file.path()).unwrap();
+    create(&mut links);
+    drop(links);
+    assert!(matches!(
+        FileLinks::<u32>::open(file.path()),
+        Err(Error::AllocFailed(error)) if error.kind() == io::ErrorKind::InvalidData
+    ));
+
+    let constants = LinksConstants::via_ranges(100..=u64::MAX, None);
+    assert!(matches!(
+        FileLinks::<u64>::with_constants(file.path(), constants),
+        Err(Error::AllocFailed(error)) if error.kind() == io::ErrorKind::InvalidData
+    ));
+    assert_eq!(
+        FileLinks::<u64>::open(file.path())
+            .unwrap()
+            .count_links(&[]),
+        1
+    );
+}
+
+#[test]
+fn damaged_headers_are_rejected() {
+    // Allocated, free, count, source root and target root, then the magic,
+    // the link size and the internal range start, followed by one zeroed
+    // record.
+    for header in [
+        [5, 0, 0, 0, 0],
+        [1, 2, 1, 1, 1],
+        [1, 0, 2, 1, 1],
+        [1, 0, 1, 7, 1],
+    ] {
+        let file = NamedTempFile::new().unwrap();
+        let mut words = [0u64; 16];
+        words[..5].copy_from_slice(&header);
+        words[5..8].copy_from_slice(&[u64::from(b'L'), 8, 1]);
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+        fs::write(file.path(), bytes).unwrap();
+        assert!(matches!(
+            FileLinks::<u64>::open(file.path()),
+            Err(Error::AllocFailed(error)) if error.kind() == io::ErrorKind::InvalidData
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use platform_data::Flow;
+use platform_data::{Flow, FlowWith};
 use std::ops::ControlFlow;
 
 #[test]
@@ -136,3 +136,45 @@ fn test_flow_debug() {
     assert_eq!(format!("{:?}", Flow::Continue), "Continue");
     assert_eq!(format!("{:?}", Flow::Break), "Break");
 }
+
+#[test]
+fn test_flow_with_control_flow_round_trip() {
+    let found: FlowWith<usize> = (0..100)
+        .try_for_each(|i| {
+            if i * i > 50 {
+                FlowWith::Break(i)
+            } else {
+                FlowWith::Continue
+            }
+            .into_control_flow()
+        })
+        .into();
+    assert_eq!(found, FlowWith::Break(8));
+    assert_eq!(ControlFlow::from(found), ControlFlow::Break(8));
+    assert_eq!(
+        FlowWith::<usize>::from(ControlFlow::Continue(())),
+        FlowWith::Continue
+    );
+    assert_eq!(
+        ControlFlow::<usize>::from(FlowWith::Continue),
+        ControlFlow::Continue(())
+    );
+}
+
+#[test]
+fn test_flow_with_values() {
+    let found = FlowWith::Break(21);
+    assert!(found.is_break() && !found.is_continue());
+    assert_eq!(found.map_break(|v| v * 2).break_value(), Some(42));
+    assert_eq!(FlowWith::<i32>::Continue.break_value(), None);
+    assert_eq!(FlowWith::from(Some(1)), FlowWith::Break(1));
+    assert_eq!(FlowWith::<i32>::from(None), FlowWith::Continue);
+}
+
+#[test]
+fn test_flow_with_and_flow() {
+    assert_eq!(Flow::from(FlowWith::Break("found")), Flow::Break);
+    assert_eq!(Flow::from(FlowWith::<()>::Continue), Flow::Continue);
+    assert_eq!(FlowWith::from(Flow::Break), FlowWith::Break(()));
+    assert_eq!(FlowWith::from(Flow::Continue), FlowWith::Continue);
+}
@@ -0,0 +1,128 @@
+use platform_data::{DeleteMode, HistoryLinks, Link, Links, LinksExt, MemoryLinks, UsageLinks};
+
+fn history() -> HistoryLinks<u64, MemoryLinks<u64>> {
+    HistoryLinks::new(MemoryLinks::new())
+}
+
+fn all(links: &HistoryLinks<u64, impl Links<u64>>) -> Vec<Link<u64>> {
+    links.iter(&[]).unwrap().collect()
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+
+    testing::run(history);
+}
+
+#[test]
+fn undo_and_redo_every_operation() {
+    let mut links = history();
+    let a = links.create().unwrap();
+    links.update(a, 100, 200).unwrap();
+    let b = links.create().unwrap();
+    links.delete(a).unwrap();
+    let states = [
+        vec![],
+        vec![Link::new(a, 0, 0)],
+        vec![Link::new(a, 100, 200)],
+        vec![Link::new(a, 100, 200), Link::new(b, 0, 0)],
+        vec![Link::new(b, 0, 0)],
+    ];
+    assert_eq!(links.undo_len(), 4);
+
+    for state in states.iter().rev().skip(1) {
+        assert!(links.undo().unwrap());
+        assert_eq!(&all(&links), state);
+    }
+    assert!(!links.undo().unwrap());
+    assert_eq!(links.redo_len(), 4);
+
+    for state in states.iter().skip(1) {
+        assert!(links.redo().unwrap());
+        assert_eq!(&all(&links), state);
+    }
+    assert!(!links.redo().unwrap());
+}
+
+#[test]
+fn groups_undo_as_one_step() {
+    let mut links = history();
+    links.begin_group();
+    let a = links.create_link(100, 200).unwrap();
+    links.begin_group();
+    links.create_link(a, a).unwrap();
+    links.end_group();
+    assert_eq!(links.undo_len(), 1);
+    links.create_link(300, 400).unwrap();
+    links.end_group();
+    links.end_group();
+    assert_eq!(links.undo_len(), 1);
+
+    links.undo().unwrap();
+    assert!(all(&links).is_empty());
+    links.redo().unwrap();
+    assert_eq!(links.count_all(), 3);
+
+    // Undo closes an open group.
+    links.begin_group();
+    links.delete(a + 2).unwrap();
+    links.undo().unwrap();
+    assert_eq!(links.count_all(), 3);
+    assert_eq!(links.undo_len(), 1);
+}
+
+#[test]
+fn new_steps_clear_redo() {
+    let mut links = history();
+    let a = links.create_link(100, 200).unwrap();
+    links.undo().unwrap();
+    assert!(links.can_redo());
+    let b = links.create().unwrap();
+    assert!(!links.can_redo());
+    assert_eq!(all(&links), [Link::new(a, 0, 0), Link::new(b, 0, 0)]);
+    assert!(!links.redo().unwrap());
+}
+
+#[test]
+fn limit_drops_the_oldest_steps() {
+    let mut links = HistoryLinks::with_limit(MemoryLinks::<u64>::new(), 2);
+    let a = links.create().unwrap();
+    for source in 100..104 {
+        links.update(a, source, source).unwrap();
+    }
+    assert_eq!(links.undo_len(), 2);
+    while links.undo().unwrap() {}
+    assert_eq!(all(&links), [Link::new(a, 101, 101)]);
+
+    links.set_limit(Some(1));
+    assert_eq!(links.limit(), Some(1));
+    links.redo().unwrap();
+    links.redo().unwrap();
+    assert_eq!(links.undo_len(), 1);
+    assert_eq!(all(&links), [Link::new(a, 103, 103)]);
+}
+
+#[test]
+fn a_cascade_is_one_step() {
+    let usage = UsageLinks::new(MemoryLinks::<u64>::new(), DeleteMode::Cascade);
+    let mut links = HistoryLinks::new(usage);
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create_link(a, 300).unwrap();
+    let c = links.create_link(b, a).unwrap();
+    let before = all(&links);
+
+    links.delete(a).unwrap();
+    assert!(all(&links).is_empty());
+    links.undo().unwrap();
+    assert_eq!(all(&links), before);
+    assert_eq!(
+        before,
+        [
+            Link::new(a, 100, 200),
+            Link::new(b, a, 300),
+            Link::new(c, b, a)
+        ]
+    );
+}
@@ -1,9 +1,153 @@
-use platform_data::{AddrToRaw, Hybrid, RawToAddr};
+use std::convert::TryFrom;
+
+use platform_data::{
+    AddrToRaw, Hybrid, HybridError, HybridKind, LinkType, LinksConstants, RawToAddr,
+};
+use quickcheck::TestResult;
 use quickcheck_macros::quickcheck;
 
 #[quickcheck]
-fn basic(orig: usize) -> bool {
-    RawToAddr.convert(AddrToRaw.convert(orig)) == orig && Hybrid::new(orig).abs() == orig
+fn basic(orig: usize) -> TestResult {
+    // Larger addresses do not fit in the signed view.
+    if orig > Hybrid::<usize>::half() {
+        return TestResult::discard();
+    }
+    TestResult::from_bool(
+        RawToAddr.convert(AddrToRaw.convert(orig)) == orig && Hybrid::new(orig).abs() == orig,
+    )
+}
+
+fn external_abs<T: LinkType>(x: T) -> bool {
+    // Halving keeps `x` within the external range of the width.
+    let x = x / T::funty(2);
+    Hybrid::external(x).abs() == x && Hybrid::external(x + T::funty(1)).abs() == x + T::funty(1)
+}
+
+#[quickcheck]
+fn external_abs_u8(x: u8) -> bool {
+    external_abs(x)
+}
+
+#[quickcheck]
+fn external_abs_u16(x: u16) -> bool {
+    external_abs(x)
+}
+
+#[quickcheck]
+fn external_abs_u32(x: u32) -> bool {
+    external_abs(x)
+}
+
+#[quickcheck]
+fn external_abs_u64(x: u64) -> bool {
+    external_abs(x)
+}
+
+#[quickcheck]
+fn external_abs_u128(x: u128) -> bool {
+    external_abs(x)
+}
+
+#[quickcheck]
+fn external_abs_usize(x: usize) -> bool {
+    external_abs(x)
+}
+
+#[quickcheck]
+fn i128_round_trip(value: i16) -> bool {
+    let value = i128::from(value);
+    let hybrid = Hybrid::<u16>::try_from(value).unwrap();
+    i128::from(hybrid) == value && Hybrid::<u64>::try_from(value).map(i128::from) == Ok(value)
+}
+
+#[quickcheck]
+fn checked_matches_i128(a: i8, b: i8) -> bool {
+    let (x, y) = (
+        Hybrid::<u8>::try_from(a as i128).unwrap(),
+        Hybrid::try_from(b as i128).unwrap(),
+    );
+    x.checked_add(y).map(i128::from) == a.checked_add(b).map(i128::from)
+        && x.checked_sub(y).map(i128::from) == a.checked_sub(b).map(i128::from)
+}
+
+#[test]
+fn test_hybrid_signed_edges() {
+    assert_eq!(Hybrid::<u8>::external(128).abs(), 128);
+    assert_eq!(i128::from(Hybrid::<u8>::external(128)), -128);
+    assert_eq!(i128::from(Hybrid::<u8>::internal(127)), 127);
+    assert_eq!(i128::from(Hybrid::<u128>::external(1 << 127)), i128::MIN);
+    assert_eq!(
+        i128::from(Hybrid::<u128>::internal(u128::MAX / 2)),
+        i128::MAX
+    );
+    assert_eq!(
+        Hybrid::<u128>::try_from(i128::MIN),
+        Ok(Hybrid::external(1 << 127))
+    );
+
+    assert_eq!(
+        Hybrid::<u8>::try_from(128),
+        Err(HybridError::OutOfRange(128))
+    );
+    assert_eq!(
+        Hybrid::<u8>::try_from(-129),
+        Err(HybridError::OutOfRange(-129))
+    );
+}
+
+#[test]
+fn test_hybrid_signum() {
+    let (negative, zero, positive) = (
+        Hybrid::<u32>::external(3),
+        Hybrid::<u32>::internal(0),
+        Hybrid::<u32>::internal(3),
+    );
+    assert_eq!([negative, zero, positive].map(|h| h.signum()), [-1, 0, 1]);
+    assert!(negative.is_negative() && !negative.is_positive());
+    assert!(!zero.is_negative() && !zero.is_positive());
+    assert!(positive.is_positive() && !positive.is_negative());
+}
+
+#[test]
+fn test_hybrid_arithmetic() {
+    let (a, b) = (Hybrid::<u8>::internal(100), Hybrid::<u8>::external(100));
+    assert_eq!(a.checked_add(b), Some(Hybrid::internal(0)));
+    assert_eq!(b.checked_sub(a), None);
+    assert_eq!(a.checked_add(a), None);
+    assert_eq!(a.wrapping_add(a), Hybrid::external(56));
+    assert_eq!(b.wrapping_sub(a), Hybrid::internal(56));
+    assert_eq!(
+        Hybrid::<u8>::internal(3).checked_sub(Hybrid::internal(5)),
+        Some(Hybrid::external(2))
+    );
+}
+
+#[test]
+fn test_hybrid_display() {
+    assert_eq!(Hybrid::<u64>::internal(42).to_string(), "+42");
+    assert_eq!(Hybrid::<u64>::external(42).to_string(), "-42");
+    assert_eq!(Hybrid::<u64>::internal(0).to_string(), "0");
+    assert_eq!(format!("{:?}", Hybrid::<u8>::external(7)), "Hybrid(-7)");
+}
+
+#[test]
+fn test_hybrid_try_external() {
+    let constants = LinksConstants::<u32>::via_ranges(1..=1000, Some(u32::MAX - 99..=u32::MAX));
+    assert_eq!(
+        Hybrid::try_external(100, &constants),
+        Ok(Hybrid::external(100))
+    );
+    assert_eq!(
+        Hybrid::try_external(101, &constants),
+        Err(HybridError::NotExternal(101))
+    );
+    assert_eq!(
+        Hybrid::try_external(0, &constants),
+        Err(HybridError::NotExternal(0))
+    );
+
+    let internal_only = LinksConstants::<u32>::via_ranges(1..=1000, None);
+    assert!(Hybrid::try_external(1, &internal_only).is_err());
 }
 
 #[test]
@@ -52,9 +196,10 @@ fn test_hybrid_is_internal() {
 
 #[test]
 fn test_hybrid_is_external() {
-    // Zero is considered external
+    // Zero is null, neither internal nor external
     let zero = Hybrid::<usize>::internal(0);
-    assert!(zero.is_external());
+    assert!(!zero.is_external() && !zero.is_internal());
+    assert_eq!(zero.kind(), HybridKind::Null);
 
     // External-created hybrids should be external
     let external = Hybrid::<usize>::external(1);
@@ -110,6 +255,11 @@ fn test_hybrid_ord() {
     let h2 = Hybrid::<usize>::internal(20);
     assert!(h1 < h2);
     assert!(h2 > h1);
+
+    // External references are negative and sort first.
+    let e1 = Hybrid::<usize>::external(1);
+    let e2 = Hybrid::<usize>::external(2);
+    assert!(e2 < e1 && e1 < Hybrid::internal(0));
 }
 
 #[test]
@@ -139,3 +289,67 @@ fn test_different_types() {
     let h64 = Hybrid::<u64>::new(10000);
     assert!(h64.is_internal());
 }
+
+#[test]
+fn test_const_hybrid_matches_runtime_one() {
+    const HALF: u32 = Hybrid::<u32>::HALF;
+    const EXTERNAL: Hybrid<u64> = Hybrid::<u64>::external_const(7);
+    const INTERNAL: Hybrid<u8> = Hybrid::<u8>::internal_const(7);
+
+    assert_eq!(HALF, Hybrid::<u32>::half());
+    assert_eq!(EXTERNAL, Hybrid::external(7));
+    assert_eq!(INTERNAL, Hybrid::internal(7));
+    assert_eq!(Hybrid::<usize>::external_const(0), Hybrid::external(0));
+}
+
+#[test]
+fn test_every_u8_is_classified_once() {
+    let half = Hybrid::<u8>::half();
+    for value in 0..=u8::MAX {
+        let hybrid = Hybrid::new(value);
+        let expected = match value {
+            0 => HybridKind::Null,
+            _ if value <= half => HybridKind::Internal,
+            _ => HybridKind::External,
+        };
+        assert_eq!(hybrid.kind(), expected, "{}", value);
+        assert_eq!(hybrid.is_internal(), expected == HybridKind::Internal);
+        assert_eq!(hybrid.is_external(), expected == HybridKind::External);
+        assert_eq!(hybrid.is_zero(), expected == HybridKind::Null);
+        assert_eq!(hybrid.signum() < 0, hybrid.is_external());
+    }
+}
+
+#[test]
+fn test_every_u8_converts_consistently() {
+    let constants = LinksConstants::<u8>::external();
+    for value in 0..=u8::MAX {
+        let raw = Hybrid::new(value);
+        let is_address = u16::from(value) <= u16::from(Hybrid::<u8>::half()) + 1;
+
+        // Addresses round-trip and map to external references, null to null.
+        if is_address {
+            let reference = AddrToRaw.convert(value);
+            assert_eq!(RawToAddr.convert(reference), value, "{}", value);
+            assert_eq!(Hybrid::new(reference).is_external(), value != 0);
+            assert_eq!(constants.is_external(reference), value != 0);
+        }
+
+        // External raw values come back from their address.
+        if raw.is_external() {
+            assert_eq!(AddrToRaw.convert(RawToAddr.convert(value)), value);
+        }
+
+        // The default external layout splits the values like `HybridKind`.
+        assert_eq!(constants.is_external(value), raw.is_external(), "{}", value);
+        if constants.is_internal(value) || constants.is_reserved(value) {
+            assert!(raw.is_internal(), "{}", value);
+        } else {
+            assert!(!raw.is_internal(), "{}", value);
+        }
+        assert_eq!(
+            LinksConstants::<u8>::EXTERNAL.is_external(value),
+            raw.is_external()
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use platform_data::{Flow, Link, Links, LinksExt, MemoryLinks};
+
+fn store() -> MemoryLinks<usize> {
+    let mut links = MemoryLinks::new();
+    let a = links.create_link(1, 1).unwrap();
+    for target in 0..10 {
+        let index = links.create().unwrap();
+        links.update(index, a, index - target % 2).unwrap();
+    }
+    links
+}
+
+#[test]
+fn agrees_with_count_and_each() {
+    let links = store();
+    let any = links.constants_links().any;
+    for query in [
+        vec![],
+        vec![any],
+        vec![3],
+        vec![any, 1, any],
+        vec![any, any, 5],
+    ] {
+        let collected: Vec<_> = links.iter(&query).unwrap().collect();
+        assert_eq!(collected.len(), links.count_links(&query));
+
+        let mut each = vec![];
+        links
+            .each_links(&query, &mut |link| {
+                each.push(Link::from_slice(link, &links.constants_links()));
+                Flow::Continue
+            })
+            .unwrap();
+        assert_eq!(collected, each);
+    }
+}
+
+#[test]
+fn works_with_iterator_adapters() {
+    let links = store();
+    let firsts: Vec<_> = links
+        .iter(&[])
+        .unwrap()
+        .take_while(|link| link.index < 4)
+        .map(|link| link.index)
+        .collect();
+    assert_eq!(firsts, [1, 2, 3]);
+
+    let mut pairs = links
+        .iter(&[])
+        .unwrap()
+        .zip(links.iter(&[]).unwrap().skip(1));
+    assert!(pairs.all(|(a, b)| a.index < b.index));
+}
+
+#[test]
+fn dropped_early() {
+    let links = store();
+    let mut iter = links.iter(&[]).unwrap();
+    assert_eq!(iter.len(), 11);
+    assert_eq!(iter.next().map(|link| link.index), Some(1));
+    drop(iter);
+    assert_eq!(links.iter(&[]).unwrap().count(), 11);
+}
+
+#[test]
+fn does_not_borrow_storage() {
+    let mut links = store();
+    // later links refer to earlier ones, so delete them first
+    for link in links.iter(&[]).unwrap().rev() {
+        if link.is_full() {
+            continue;
+        }
+        links.delete(link.index).unwrap();
+    }
+    assert_eq!(links.count_all(), 1);
+}
+
+#[test]
+fn empty_query_result() {
+    let links = MemoryLinks::<usize>::new();
+    let mut iter = links.iter(&[]).unwrap();
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
@@ -0,0 +1,98 @@
+use platform_data::{
+    read_handler, write_handler, Flow, Link, Links, LinksConstants, LinksExt, MemoryLinks,
+};
+
+#[test]
+fn from_slice_uses_default_parts() {
+    let constants = LinksConstants::<u64>::new();
+    assert_eq!(Link::from_slice(&[1, 2, 3], &constants), Link::new(1, 2, 3));
+    assert_eq!(Link::from_slice(&[], &constants), Link::nothing());
+}
+
+#[test]
+fn custom_target_part() {
+    let constants = LinksConstants::<u64>::via_external(4, false);
+    let link = Link::new(1, 2, 3);
+    let slice = link.to_vec(&constants);
+    assert_eq!(slice, [1, 2, 0, 0, 3]);
+    assert_eq!(Link::from_slice(&slice, &constants), link);
+}
+
+#[test]
+#[should_panic]
+fn from_short_slice_panics() {
+    let constants = LinksConstants::<u64>::new();
+    Link::from_slice(&[1, 2], &constants);
+}
+
+#[test]
+fn array_conversions() {
+    let link: Link<u8> = [1, 2, 3].into();
+    assert_eq!(link, Link::new(1, 2, 3));
+    let array: [u8; 3] = link.into();
+    assert_eq!(array, [1, 2, 3]);
+}
+
+#[test]
+fn points() {
+    assert!(Link::point(5u32).is_full());
+    assert!(Link::new(5u32, 5, 1).is_partial());
+    assert!(!Link::new(5u32, 5, 1).is_full());
+    assert!(!Link::new(5u32, 1, 2).is_partial());
+    assert!(Link::<u32>::nothing().is_nothing());
+}
+
+#[test]
+fn pattern_matching_in_handlers() {
+    let mut links = MemoryLinks::<usize>::new();
+    let constants = links.constants_links();
+    let a = links.create_link(1, 1).unwrap();
+    let b = links.create().unwrap();
+
+    let mut changes = vec![];
+    links
+        .update_links(
+            &[b],
+            &Link::new(b, a, b).to_vec(&constants),
+            &mut write_handler(&constants, |before, after| {
+                changes.push((before, after));
+                Flow::Continue
+            }),
+        )
+        .unwrap();
+    assert_eq!(changes, [(Link::new(b, 0, 0), Link::new(b, a, b))]);
+
+    let mut targets = vec![];
+    links
+        .each_links(
+            &[constants.any, a, constants.any],
+            &mut read_handler(&constants, |Link { target, .. }| {
+                targets.push(target);
+                Flow::Continue
+            }),
+        )
+        .unwrap();
+    assert_eq!(targets, [a, b]);
+}
+
+#[test]
+fn write_handler_reports_nothing_for_missing_sides() {
+    let mut links = MemoryLinks::<usize>::new();
+    let constants = links.constants_links();
+    let mut changes = vec![];
+    {
+        let mut handler = write_handler(&constants, |before, after| {
+            changes.push((before, after));
+            Flow::Continue
+        });
+        links.create_links(&[], &mut handler).unwrap();
+        links.delete_links(&[1], &mut handler).unwrap();
+    }
+    assert_eq!(
+        changes,
+        [
+            (Link::nothing(), Link::new(1, 0, 0)),
+            (Link::new(1, 0, 0), Link::nothing()),
+        ]
+    );
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "std")]
+
+use platform_data::{
+    read_lino, to_lino, Error, Link, LinksExt, LinoError, LinoPosition, MemoryLinks,
+};
+
+fn at(line: usize, column: usize) -> LinoPosition {
+    LinoPosition { line, column }
+}
+
+#[test]
+fn read_numbered_links() {
+    let mut links = MemoryLinks::<usize>::new();
+    read_lino(&mut links, "(1: 1 1)\n(3: 1 2)\n(2: 3 1)").unwrap();
+    assert_eq!(links.get_link(1), Some(Link::new(1, 1, 1)));
+    assert_eq!(links.get_link(2), Some(Link::new(2, 3, 1)));
+    assert_eq!(links.get_link(3), Some(Link::new(3, 1, 2)));
+    assert_eq!(links.count_all(), 3);
+}
+
+#[test]
+fn numbered_index_updates_existing_link() {
+    let mut links = MemoryLinks::<usize>::new();
+    read_lino(&mut links, "(1: 1 1) (1: 0 1)").unwrap();
+    assert_eq!(links.get_link(1), Some(Link::new(1, 0, 1)));
+    assert_eq!(links.count_all(), 1);
+}
+
+#[test]
+fn read_named_links_with_forward_references() {
+    let mut links = MemoryLinks::<usize>::new();
+    let names = read_lino(
+        &mut links,
+        "(a: b b) ('long name': a \"long name\") (b: a 0)",
+    )
+    .unwrap();
+    let (a, b, long) = (names["a"], names["b"], names["long name"]);
+    assert_eq!(links.get_link(a), Some(Link::new(a, b, b)));
+    assert_eq!(links.get_link(b), Some(Link::new(b, a, 0)));
+    assert_eq!(links.get_link(long), Some(Link::new(long, a, long)));
+    assert_eq!(links.count_all(), 3);
+}
+
+#[test]
+fn nested_links_are_shared() {
+    let mut links = MemoryLinks::<usize>::new();
+    read_lino(&mut links, "(1: 1 1) (10: (1 1) (1 (1 0))) (20: (1 0) 10)").unwrap();
+    let inner = links.search(1, 0).unwrap();
+    let outer = links.search(1, inner).unwrap();
+    assert_eq!(links.get_link(10), Some(Link::new(10, 1, outer)));
+    assert_eq!(links.get_link(20), Some(Link::new(20, inner, 10)));
+    assert_eq!(links.count_all(), 5);
+}
+
+#[test]
+fn print_and_read_back() {
+    let mut links = MemoryLinks::<u32>::new();
+    read_lino(&mut links, "(x: x y) (y: (x x) 7) (7: 0 0)").unwrap();
+    let text = to_lino(&links).unwrap();
+    assert_eq!(text.lines().count(), 4);
+
+    let mut copy = MemoryLinks::<u32>::new();
+    read_lino(&mut copy, &text).unwrap();
+    assert_eq!(to_lino(&copy).unwrap(), text);
+    assert_eq!(
+        copy.iter(&[]).unwrap().collect::<Vec<_>>(),
+        links.iter(&[]).unwrap().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn syntax_errors_report_positions() {
+    let mut links = MemoryLinks::<u8>::new();
+    let error = |text| read_lino(&mut MemoryLinks::<u8>::new(), text).unwrap_err();
+
+    assert!(matches!(
+        error("(1: 1 1)\n  (2: 1"),
+        LinoError::Unexpected { position, found, .. } if position == at(2, 8) && found == "end of input"
+    ));
+    assert!(matches!(
+        error("(1: 1 1) x"),
+        LinoError::Unexpected { position, .. } if position == at(1, 10)
+    ));
+    assert!(matches!(
+        error("(1 2: 1 1)"),
+        LinoError::Unexpected { position, found, .. } if position == at(1, 5) && found == "`:`"
+    ));
+    assert!(matches!(
+        error("(1: 1 1)\n(2: 1 1 1)"),
+        LinoError::Arity { position, found: 3 } if position == at(2, 1)
+    ));
+    assert!(matches!(
+        error("(1: 1 256)"),
+        LinoError::Overflow { position, number } if position == at(1, 7) && number == "256"
+    ));
+    assert!(matches!(
+        error("(a: a b)"),
+        LinoError::Undefined { position, name } if position == at(1, 7) && name == "b"
+    ));
+    assert!(matches!(
+        error("(1: 1 5)"),
+        LinoError::Missing { position, index: 5 } if position == at(1, 7)
+    ));
+    assert!(matches!(
+        error(&"(".repeat(300)),
+        LinoError::TooDeep { position, limit: 256 } if position == at(1, 257)
+    ));
+
+    read_lino(&mut links, "(1: 1 1) (2: 1").unwrap_err();
+    assert_eq!(links.count_all(), 0);
+}
+
+#[test]
+fn storage_errors_report_positions() {
+    let mut links = MemoryLinks::<u8>::new();
+    let error = read_lino(&mut links, "(1: 1 1)\n(250: 1 1)").unwrap_err();
+    assert!(matches!(
+        error,
+        LinoError::Links { position, error: Error::LimitReached(_) } if position == at(2, 2)
+    ));
+    assert_eq!(error.to_string().split(':').next(), Some("2"));
+    // Links read before the failure stay in place.
+    assert_eq!(links.count_all(), 1);
+}
@@ -0,0 +1,254 @@
+use platform_data::{Error, Flow, Links, LinksConstants, MemoryLinks};
+
+fn create(links: &mut MemoryLinks<usize>) -> usize {
+    let mut index = 0;
+    links
+        .create_links(&[], &mut |before, after| {
+            assert!(before.is_empty());
+            index = after[0];
+            Flow::Continue
+        })
+        .unwrap();
+    index
+}
+
+fn update(links: &mut MemoryLinks<usize>, index: usize, source: usize, target: usize) {
+    links
+        .update_links(&[index], &[index, source, target], &mut |_, _| {
+            Flow::Continue
+        })
+        .unwrap();
+}
+
+#[test]
+fn create_allocates_sequential_addresses() {
+    let mut links = MemoryLinks::new();
+    assert_eq!(create(&mut links), 1);
+    assert_eq!(create(&mut links), 2);
+    assert_eq!(create(&mut links), 3);
+    assert_eq!(links.count_links(&[]), 3);
+}
+
+#[test]
+fn create_reports_empty_link() {
+    let mut links = MemoryLinks::<usize>::new();
+    links
+        .create_links(&[], &mut |before, after| {
+            assert_eq!(before, []);
+            assert_eq!(after, [1, 0, 0]);
+            Flow::Continue
+        })
+        .unwrap();
+}
+
+#[test]
+fn create_at_requested_address() {
+    let mut links = MemoryLinks::<usize>::new();
+    links
+        .create_links(&[5], &mut |_, after| {
+            assert_eq!(after, [5, 0, 0]);
+            Flow::Continue
+        })
+        .unwrap();
+
+    // gaps left by the requested address are reused first
+    assert_eq!(create(&mut links), 1);
+    assert!(matches!(
+        links.create_links(&[5], &mut |_, _| Flow::Continue),
+        Err(Error::AlreadyExists(index)) if *index == 5
+    ));
+}
+
+#[test]
+fn unaffordable_address_is_reported() {
+    let mut links = MemoryLinks::<usize>::new();
+    assert!(matches!(
+        links.create_links(&[usize::MAX / 4], &mut |_, _| Flow::Continue),
+        Err(Error::AllocFailed(_))
+    ));
+    assert_eq!(links.count_links(&[]), 0);
+    assert_eq!(create(&mut links), 1);
+}
+
+#[test]
+fn update_reports_before_and_after() {
+    let mut links = MemoryLinks::new();
+    let index = create(&mut links);
+    links
+        .update_links(&[index], &[index, 1, 1], &mut |before, after| {
+            assert_eq!(before, [index, 0, 0]);
+            assert_eq!(after, [index, 1, 1]);
+            Flow::Continue
+        })
+        .unwrap();
+}
+
+#[test]
+fn delete_reuses_address() {
+    let mut links = MemoryLinks::new();
+    let a = create(&mut links);
+    let b = create(&mut links);
+    update(&mut links, b, b, b);
+
+    links
+        .delete_links(&[a], &mut |before, after| {
+            assert_eq!(before, [a, 0, 0]);
+            assert!(after.is_empty());
+            Flow::Continue
+        })
+        .unwrap();
+
+    assert_eq!(links.count_links(&[]), 1);
+    assert_eq!(links.count_links(&[a]), 0);
+    assert_eq!(create(&mut links), a);
+}
+
+#[test]
+fn missing_links_are_reported() {
+    let mut links = MemoryLinks::<usize>::new();
+    assert!(matches!(
+        links.update_links(&[1], &[1, 1, 1], &mut |_, _| Flow::Continue),
+        Err(Error::NotExists(1))
+    ));
+    assert!(matches!(
+        links.delete_links(&[1], &mut |_, _| Flow::Continue),
+        Err(Error::NotExists(1))
+    ));
+}
+
+#[test]
+fn delete_with_usages_fails() {
+    let mut links = MemoryLinks::new();
+    let a = create(&mut links);
+    let b = create(&mut links);
+    update(&mut links, a, a, a);
+    update(&mut links, b, a, b);
+
+    match links.delete_links(&[a], &mut |_, _| Flow::Continue) {
+        Err(Error::HasUsages(usages)) => assert_eq!(usages, [vec![b, a, b]]),
+        _ => panic!("expected `HasUsages`"),
+    }
+}
+
+#[test]
+fn wildcard_queries() {
+    let mut links = MemoryLinks::new();
+    let any = links.constants_links().any;
+    let a = create(&mut links);
+    let b = create(&mut links);
+    let c = create(&mut links);
+    update(&mut links, a, a, a);
+    update(&mut links, b, a, c);
+    update(&mut links, c, b, a);
+
+    assert_eq!(links.count_links(&[any]), 3);
+    assert_eq!(links.count_links(&[b]), 1);
+    assert_eq!(links.count_links(&[any, a, any]), 2);
+    assert_eq!(links.count_links(&[any, any, a]), 2);
+    assert_eq!(links.count_links(&[any, a, c]), 1);
+    assert_eq!(links.count_links(&[c, b, a]), 1);
+    assert_eq!(links.count_links(&[c, a, a]), 0);
+    // two-part queries match the value as either source or target
+    assert_eq!(links.count_links(&[any, c]), 1);
+    assert_eq!(links.count_links(&[any, a]), 3);
+
+    let mut found = vec![];
+    links
+        .each_links(&[any, a, any], &mut |link| {
+            found.push(link.to_vec());
+            Flow::Continue
+        })
+        .unwrap();
+    assert_eq!(found, [vec![a, a, a], vec![b, a, c]]);
+}
+
+#[test]
+fn each_stops_on_break() {
+    let mut links = MemoryLinks::new();
+    for _ in 0..10 {
+        create(&mut links);
+    }
+
+    let mut visited = 0;
+    let flow = links
+        .each_links(&[], &mut |_| {
+            visited += 1;
+            if visited == 3 {
+                Flow::Break
+            } else {
+                Flow::Continue
+            }
+        })
+        .unwrap();
+    assert_eq!(flow, Flow::Break);
+    assert_eq!(visited, 3);
+}
+
+#[test]
+fn limit_from_internal_range() {
+    // six addresses at the end of the range are reserved for constants
+    let constants = LinksConstants::via_ranges(1..=8, None);
+    let mut links = MemoryLinks::<usize>::with_constants(constants);
+    assert_eq!(create(&mut links), 1);
+    assert_eq!(create(&mut links), 2);
+    assert!(matches!(
+        links.create_links(&[], &mut |_, _| Flow::Continue),
+        Err(Error::LimitReached(2))
+    ));
+}
+
+#[test]
+fn indexed_queries_agree_with_scan() {
+    let mut links = MemoryLinks::new();
+    let any = links.constants_links().any;
+    let count = 50;
+    for _ in 0..count {
+        create(&mut links);
+    }
+    for index in 1..=count {
+        update(&mut links, index, index % 7 + 1, index % 5 + 1);
+    }
+    for index in (1..=count).step_by(3).filter(|&index| index > 7) {
+        links
+            .delete_links(&[index], &mut |_, _| Flow::Continue)
+            .unwrap();
+    }
+
+    let mut all = vec![];
+    links
+        .each_links(&[], &mut |link| {
+            all.push((link[0], link[1], link[2]));
+            Flow::Continue
+        })
+        .unwrap();
+
+    for value in 1..=8 {
+        let by_source = all.iter().filter(|link| link.1 == value).count();
+        let by_target = all.iter().filter(|link| link.2 == value).count();
+        let by_any = all
+            .iter()
+            .filter(|link| link.1 == value || link.2 == value)
+            .count();
+        assert_eq!(links.count_links(&[any, value, any]), by_source);
+        assert_eq!(links.count_links(&[any, any, value]), by_target);
+        assert_eq!(links.count_links(&[any, value]), by_any);
+
+        let mut visited = 0;
+        links
+            .each_links(&[any, value], &mut |link| {
+                assert!(link[1] == value || link[2] == value);
+                visited += 1;
+                Flow::Continue
+            })
+            .unwrap();
+        assert_eq!(visited, by_any);
+
+        for target in 1..=8 {
+            let pairs = all
+                .iter()
+                .filter(|link| link.1 == value && link.2 == target)
+                .count();
+            assert_eq!(links.count_links(&[any, value, target]), pairs);
+        }
+    }
+}
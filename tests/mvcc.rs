@@ -0,0 +1,151 @@
+#![cfg(feature = "std")]
+
+use std::thread;
+
+use platform_data::{Error, ErrorKind, Flow, Link, Links, LinksExt, MemoryLinks, VersionedLinks};
+
+fn versioned() -> VersionedLinks<u64, MemoryLinks<u64>> {
+    VersionedLinks::new(MemoryLinks::new())
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+
+    testing::run(versioned);
+}
+
+#[test]
+fn snapshots_are_frozen() {
+    let mut links = versioned();
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create_link(a, 300).unwrap();
+    let c = links.create_link(400, 500).unwrap();
+
+    let snapshot = links.snapshot();
+    assert_eq!(snapshot.version(), links.version());
+    links.update(a, 600, 700).unwrap();
+    links.update(a, 800, 900).unwrap();
+    let d = links.create_link(a, a).unwrap();
+    links.delete(c).unwrap();
+
+    let frozen = [
+        Link::new(a, 100, 200),
+        Link::new(b, a, 300),
+        Link::new(c, 400, 500),
+    ];
+    assert_eq!(snapshot.iter(&[]).unwrap().collect::<Vec<_>>(), frozen);
+    assert_eq!(snapshot.count_all(), 3);
+    assert_eq!(snapshot.get_link(d), None);
+    assert_eq!(snapshot.search(400, 500), Some(c));
+
+    // Queries see the old values, not the live ones.
+    let any = links.constants_links().any;
+    assert_eq!(snapshot.count_links(&[any, a]), 1);
+    assert_eq!(snapshot.count_links(&[any, 800, 900]), 0);
+    assert_eq!(links.count_links(&[any, a]), 2);
+    assert_eq!(snapshot.count_links(&[any, 100, 200]), 1);
+}
+
+#[test]
+fn snapshot_lookups_and_counts_see_the_old_links() {
+    let mut links = versioned();
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create_link(100, 300).unwrap();
+    let snapshot = links.snapshot();
+    links.update(a, 400, 200).unwrap();
+    let c = links.create_link(100, 500).unwrap();
+    links.delete(b).unwrap();
+
+    let any = links.constants_links().any;
+    assert_eq!(snapshot.get_link(a), Some(Link::new(a, 100, 200)));
+    assert_eq!(snapshot.get_link(b), Some(Link::new(b, 100, 300)));
+    assert_eq!(snapshot.get_link(c), None);
+    assert_eq!(snapshot.count_links(&[a, 100, any]), 1);
+    assert_eq!(snapshot.count_links(&[a, 400, any]), 0);
+    assert_eq!(snapshot.count_links(&[any, 100, any]), 2);
+    assert_eq!(snapshot.count_links(&[any, any, 200]), 1);
+    assert_eq!(snapshot.count_links(&[c]), 0);
+    assert_eq!(links.count_links(&[any, 100, any]), 1);
+}
+
+#[test]
+fn snapshots_are_read_only() {
+    let links = versioned();
+    let mut snapshot = links.snapshot();
+    let error = snapshot.create().unwrap_err();
+    assert!(matches!(error, Error::ReadOnly));
+    assert_eq!(error.kind(), ErrorKind::ReadOnly);
+    assert!(matches!(snapshot.delete(1), Err(Error::ReadOnly)));
+}
+
+#[test]
+fn every_snapshot_keeps_its_version() {
+    let mut links = versioned();
+    let a = links.create_link(100, 100).unwrap();
+    let mut snapshots = vec![];
+    for source in 101..105 {
+        snapshots.push(links.snapshot());
+        links.update(a, source, source).unwrap();
+    }
+    let copy = snapshots[1].clone();
+    drop(snapshots.remove(1));
+
+    assert_eq!(copy.get_link(a), Some(Link::new(a, 101, 101)));
+    for (snapshot, source) in snapshots.iter().zip([100, 102, 103]) {
+        assert_eq!(snapshot.get_link(a), Some(Link::new(a, source, source)));
+    }
+}
+
+#[test]
+fn versions_are_reclaimed() {
+    let mut links = versioned();
+    let a = links.create_link(100, 100).unwrap();
+
+    let snapshot = links.snapshot();
+    links.update(a, 200, 200).unwrap();
+    let links = links.into_inner().unwrap_err();
+    drop(snapshot);
+    let links = links.into_inner().unwrap();
+    assert_eq!(links.get_link(a), Some(Link::new(a, 200, 200)));
+
+    // Without snapshots, nothing is kept: a new one sees the live links.
+    let mut links = VersionedLinks::new(links);
+    let old = links.snapshot();
+    links.update(a, 300, 300).unwrap();
+    drop(old);
+    let fresh = links.snapshot();
+    links.update(a, 400, 400).unwrap();
+    assert_eq!(fresh.get_link(a), Some(Link::new(a, 300, 300)));
+}
+
+#[test]
+fn scans_run_while_writers_progress() {
+    let mut links = versioned();
+    for target in 0..100 {
+        links.create_link(100, 1000 + target).unwrap();
+    }
+    let snapshot = links.snapshot();
+    thread::scope(|scope| {
+        let writer = scope.spawn(move || {
+            for index in 1..=100 {
+                links.delete(index).unwrap();
+            }
+            links.count_all()
+        });
+        let reader = scope.spawn(|| {
+            let mut seen = 0;
+            snapshot
+                .each_links(&[], &mut |link| {
+                    assert_eq!(link[1], 100);
+                    seen += 1;
+                    Flow::Continue
+                })
+                .unwrap();
+            seen
+        });
+        assert_eq!(writer.join().unwrap(), 0);
+        assert_eq!(reader.join().unwrap(), 100);
+    });
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "serde")]
+
+use std::fmt::Debug;
+
+use platform_data::{Flow, FlowWith, Hybrid, Link, LinksConstants, Point, Query};
+use serde::{de::DeserializeOwned, Serialize};
+
+fn round_trip<V: Serialize + DeserializeOwned>(value: &V) -> (V, V) {
+    let json = serde_json::to_string(value).unwrap();
+    let binary = bincode::serialize(value).unwrap();
+    (
+        serde_json::from_str(&json).unwrap(),
+        bincode::deserialize(&binary).unwrap(),
+    )
+}
+
+fn assert_round_trip<V: Serialize + DeserializeOwned + PartialEq + Debug>(value: V) {
+    let (json, binary) = round_trip(&value);
+    assert_eq!(json, value);
+    assert_eq!(binary, value);
+}
+
+#[test]
+fn constants() {
+    assert_round_trip(LinksConstants::<u64>::internal());
+    assert_round_trip(LinksConstants::<u32>::external());
+    assert_round_trip(
+        LinksConstants::<u8>::builder()
+            .internal_range(1..=100)
+            .external_range(200..=255)
+            .build()
+            .unwrap(),
+    );
+}
+
+#[test]
+fn values() {
+    assert_round_trip(Flow::Continue);
+    assert_round_trip(Flow::Break);
+    assert_round_trip(FlowWith::Break(7u32));
+    assert_round_trip(FlowWith::<u32>::Continue);
+    assert_round_trip(Hybrid::<usize>::external(42));
+    assert_round_trip(Link::new(1u64, 2, 3));
+    assert_round_trip(Query::new(&[1u32, 2, 3][..]));
+    assert_round_trip(Query::<u16>::new(vec![]));
+
+    let (json, binary) = round_trip(&Point::new(7u64, 3));
+    assert_eq!(json.into_iter().collect::<Vec<_>>(), [7, 7, 7]);
+    assert_eq!(binary.into_iter().collect::<Vec<_>>(), [7, 7, 7]);
+}
+
+#[test]
+fn json_layout() {
+    assert_eq!(
+        serde_json::to_string(&Link::new(1u64, 2, 3)).unwrap(),
+        r#"{"index":1,"source":2,"target":3}"#
+    );
+    assert_eq!(serde_json::to_string(&Flow::Break).unwrap(), r#""Break""#);
+    assert_eq!(
+        serde_json::to_string(&Hybrid::<u8>::internal(5)).unwrap(),
+        "5"
+    );
+    assert_eq!(
+        serde_json::to_string(&Query::new(&[4u8, 2][..])).unwrap(),
+        "[4,2]"
+    );
+
+    let constants: LinksConstants<u64> =
+        serde_json::from_str(&serde_json::to_string(&LinksConstants::<u64>::new()).unwrap())
+            .unwrap();
+    assert_eq!(constants.any, LinksConstants::<u64>::INTERNAL.any);
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "std")]
+
+use std::{sync::Barrier, thread};
+
+use platform_data::{Flow, Link, Links, LinksExt, MemoryLinks, SharedLinks};
+
+fn shared() -> SharedLinks<u64, MemoryLinks<u64>> {
+    SharedLinks::new(MemoryLinks::new())
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+
+    testing::run(shared);
+}
+
+#[test]
+fn handles_share_the_storage() {
+    let mut links = shared();
+    let other = links.clone();
+    let a = links.create_link(100, 200).unwrap();
+    assert_eq!(other.get_link(a), Some(Link::new(a, 100, 200)));
+
+    let links = links.into_inner().unwrap_err();
+    drop(other);
+    assert_eq!(links.into_inner().unwrap().count_all(), 1);
+}
+
+#[test]
+fn concurrent_readers_and_writers() {
+    let links = shared();
+    let barrier = Barrier::new(8);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            let mut links = links.clone();
+            let barrier = &barrier;
+            scope.spawn(move || {
+                barrier.wait();
+                for target in 0..100 {
+                    links.create_link(100, 1000 + target).unwrap();
+                }
+            });
+        }
+        for _ in 0..4 {
+            let links = links.clone();
+            let barrier = &barrier;
+            scope.spawn(move || {
+                barrier.wait();
+                for _ in 0..100 {
+                    let count = links.count_all();
+                    assert!(links.iter(&[]).unwrap().count() as u64 >= count);
+                }
+            });
+        }
+    });
+    assert_eq!(links.count_all(), 400);
+}
+
+#[test]
+fn handlers_can_call_back_into_the_store() {
+    let mut links = shared();
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create_link(a, 300).unwrap();
+
+    // A write from a read handler.
+    let mut writer = links.clone();
+    let mut created = vec![];
+    links
+        .each_links(&[], &mut |link| {
+            created.push(writer.create_link(400, link[0] + 500).unwrap());
+            Flow::Continue
+        })
+        .unwrap();
+    assert_eq!(created.len(), 2);
+    assert_eq!(links.count_all(), 4);
+
+    // A read and a write from a write handler.
+    let mut other = links.clone();
+    let flow = links
+        .delete_links(&[b], &mut |before, _| {
+            assert_eq!(other.get_link(before[0]), None);
+            other.update(created[0], before[1], before[2]).unwrap();
+            Flow::Break
+        })
+        .unwrap();
+    assert_eq!(flow, Flow::Break);
+    assert_eq!(
+        links.get_link(created[0]),
+        Some(Link::new(created[0], a, 300))
+    );
+}
+
+#[test]
+fn a_break_stops_the_remaining_calls() {
+    let mut links = shared();
+    for target in 0..5 {
+        links.create_link(100, 1000 + target).unwrap();
+    }
+    let mut seen = vec![];
+    let flow = links
+        .each_links(&[], &mut |link| {
+            seen.push(link.to_vec());
+            if seen.len() == 2 {
+                Flow::Break
+            } else {
+                Flow::Continue
+            }
+        })
+        .unwrap();
+    assert_eq!(flow, Flow::Break);
+    assert_eq!(seen, [vec![1, 100, 1000], vec![2, 100, 1001]]);
+}
+
+#[test]
+fn an_index_query_returns_the_handler_flow() {
+    let mut links = shared();
+    let a = links.create_link(100, 200).unwrap();
+    links.create_link(100, 300).unwrap();
+
+    let mut seen = vec![];
+    let flow = links
+        .each_links(&[a], &mut |link| {
+            seen.push(link.to_vec());
+            Flow::Continue
+        })
+        .unwrap();
+    assert_eq!(flow, Flow::Continue);
+    assert_eq!(seen, [vec![a, 100, 200]]);
+
+    let flow = links.each_links(&[a], &mut |_| Flow::Break).unwrap();
+    assert_eq!(flow, Flow::Break);
+    let flow = links.each_links(&[500], &mut |_| Flow::Break).unwrap();
+    assert_eq!(flow, Flow::Continue);
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "testing")]
+
+use std::cell::Cell;
+
+use platform_data::{testing, FileLinks, MemoryLinks};
+use tempfile::TempDir;
+
+#[test]
+fn memory_links_conform() {
+    testing::run(MemoryLinks::<usize>::new);
+}
+
+#[test]
+fn narrow_memory_links_conform() {
+    testing::run(MemoryLinks::<u8>::new);
+}
+
+#[test]
+fn seeded_sequences_conform() {
+    testing::sequences_from_seed(MemoryLinks::<u16>::new, 20, 42);
+}
+
+#[test]
+fn file_links_conform() {
+    let dir = TempDir::new().unwrap();
+    let files = Cell::new(0);
+    testing::run(|| {
+        files.set(files.get() + 1);
+        FileLinks::<u64>::open(dir.path().join(files.get().to_string())).unwrap()
+    });
+}
@@ -0,0 +1,127 @@
+use platform_data::{
+    Change, Error, Flow, Link, Links, LinksExt, MemoryLinks, TransactionError, TransactionLinks,
+};
+
+fn snapshot<L: Links<usize>>(links: &L) -> Vec<Link<usize>> {
+    links.iter(&[]).unwrap().collect()
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+
+    testing::run(|| TransactionLinks::new(MemoryLinks::<usize>::new()));
+}
+
+#[test]
+fn commit_keeps_changes() {
+    let mut links = TransactionLinks::new(MemoryLinks::<usize>::new());
+    let a = links.create_link(1, 1).unwrap();
+    assert_eq!(links.changes().len(), 2);
+    links.commit();
+    assert!(links.changes().is_empty());
+
+    links.rollback().unwrap();
+    assert_eq!(links.get_link(a), Some(Link::new(a, 1, 1)));
+}
+
+#[test]
+fn rollback_restores_every_kind_of_change() {
+    let mut links = TransactionLinks::new(MemoryLinks::<usize>::new());
+    let a = links.create_link(1, 1).unwrap();
+    let b = links.create_link(a, 1).unwrap();
+    let c = links.create_link(b, b).unwrap();
+    links.commit();
+    let before = snapshot(&links);
+
+    links.delete(c).unwrap();
+    links.delete(b).unwrap();
+    links.update(a, a, a).unwrap();
+    links.create_link(a, a).unwrap();
+    links.rollback().unwrap();
+
+    assert_eq!(snapshot(&links), before);
+    assert!(links.changes().is_empty());
+}
+
+#[test]
+fn records_what_handlers_receive() {
+    let mut links = TransactionLinks::new(MemoryLinks::<usize>::new());
+    let mut seen = vec![];
+    links
+        .create_links(&[], &mut |before, after| {
+            seen.push((before.to_vec(), after.to_vec()));
+            Flow::Break
+        })
+        .unwrap();
+    links.update(1, 1, 0).unwrap();
+    links.delete(1).unwrap();
+
+    assert_eq!(seen, [(vec![], vec![1, 0, 0])]);
+    assert_eq!(
+        links.changes(),
+        [
+            Change::new(Link::nothing(), Link::new(1, 0, 0)),
+            Change::new(Link::new(1, 0, 0), Link::new(1, 1, 0)),
+            Change::new(Link::new(1, 1, 0), Link::nothing()),
+        ]
+    );
+    assert!(links.changes()[0].is_create());
+    assert!(links.changes()[1].is_update());
+    assert!(links.changes()[2].is_delete());
+}
+
+#[test]
+fn nested_savepoints() {
+    let mut links = TransactionLinks::new(MemoryLinks::<usize>::new());
+    let a = links.create().unwrap();
+    let outer = links.savepoint();
+    let b = links.create().unwrap();
+    let inner = links.savepoint();
+    links.update(a, b, b).unwrap();
+
+    links.rollback_to(inner).unwrap();
+    assert_eq!(links.get_link(a), Some(Link::new(a, 0, 0)));
+    assert!(links.exists(b));
+
+    links.update(a, a, a).unwrap();
+    links.rollback_to(inner).unwrap();
+    assert_eq!(links.get_link(a), Some(Link::new(a, 0, 0)));
+
+    links.rollback_to(outer).unwrap();
+    assert!(!links.exists(b));
+    assert!(matches!(
+        links.rollback_to(inner),
+        Err(Error::Transaction(error)) if matches!(*error, TransactionError::StaleSavepoint(stale) if stale == inner)
+    ));
+
+    links.release(outer).unwrap();
+    let error = links.release(outer).unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("transaction failed: Savepoint"));
+    links.rollback().unwrap();
+    assert_eq!(links.count_all(), 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn rollback_over_file_links() {
+    use platform_data::FileLinks;
+    use tempfile::NamedTempFile;
+
+    let file = NamedTempFile::new().unwrap();
+    let mut links = TransactionLinks::new(FileLinks::<u64>::open(file.path()).unwrap());
+    let a = links.create_link(1, 1).unwrap();
+    links.commit();
+
+    let b = links.create_link(a, a).unwrap();
+    links.update(a, b, b).unwrap();
+    links.rollback().unwrap();
+
+    let links = links.into_inner();
+    assert_eq!(links.get_link(a), Some(Link::new(a, 1, 1)));
+    assert!(!links.exists(b));
+    assert_eq!(links.count_all(), 1);
+}
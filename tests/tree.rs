@@ -0,0 +1,139 @@
+use platform_data::{Flow, SizeBalancedTree, TreeNode, TreeNodes, TreeNodesMut};
+use quickcheck_macros::quickcheck;
+
+/// Links `1..=keys.len()` ordered by `keys[link - 1]`.
+struct Nodes {
+    keys: Vec<(u32, u32)>,
+    nodes: Vec<TreeNode<u32>>,
+}
+
+impl Nodes {
+    fn new(keys: &[(u8, u8)]) -> Self {
+        Self {
+            keys: keys.iter().map(|&(a, b)| (a as u32, b as u32)).collect(),
+            nodes: vec![TreeNode::default(); keys.len()],
+        }
+    }
+}
+
+impl TreeNodes<u32> for Nodes {
+    fn node(&self, link: u32) -> TreeNode<u32> {
+        self.nodes[link as usize - 1]
+    }
+
+    fn key(&self, link: u32) -> (u32, u32) {
+        self.keys[link as usize - 1]
+    }
+}
+
+impl TreeNodesMut<u32> for Nodes {
+    fn set_node(&mut self, link: u32, node: TreeNode<u32>) {
+        self.nodes[link as usize - 1] = node;
+    }
+}
+
+fn collect(tree: &SizeBalancedTree<u32>, nodes: &Nodes, primary: u32) -> Vec<u32> {
+    let mut links = vec![];
+    tree.each(nodes, primary, &mut |link| {
+        links.push(link);
+        Flow::Continue
+    });
+    links
+}
+
+fn height(nodes: &Nodes, link: u32) -> usize {
+    if link == 0 {
+        0
+    } else {
+        let node = nodes.node(link);
+        1 + height(nodes, node.left).max(height(nodes, node.right))
+    }
+}
+
+#[test]
+fn empty_tree() {
+    let tree = SizeBalancedTree::<u32>::new();
+    let nodes = Nodes::new(&[]);
+    assert!(tree.is_empty());
+    assert_eq!(tree.len(&nodes), 0);
+    assert_eq!(tree.count(&nodes, 1), 0);
+    assert_eq!(tree.search(&nodes, 1, 1), None);
+}
+
+#[test]
+fn sequential_inserts_stay_balanced() {
+    let keys: Vec<_> = (0..255).map(|i| (i as u8, 0)).collect();
+    let mut nodes = Nodes::new(&keys);
+    let mut tree = SizeBalancedTree::new();
+    for link in 1..=255 {
+        tree.insert(&mut nodes, link);
+    }
+    assert_eq!(tree.len(&nodes), 255);
+    assert!(height(&nodes, tree.root()) <= 2 * 8);
+}
+
+#[test]
+fn each_visits_in_key_order() {
+    let mut nodes = Nodes::new(&[(1, 3), (2, 0), (1, 1), (1, 3)]);
+    let mut tree = SizeBalancedTree::new();
+    for link in [4, 2, 1, 3] {
+        tree.insert(&mut nodes, link);
+    }
+    assert_eq!(collect(&tree, &nodes, 1), [3, 1, 4]);
+    assert_eq!(tree.search(&nodes, 1, 3), Some(1));
+    assert_eq!(tree.count_pair(&nodes, 1, 3), 2);
+}
+
+#[test]
+fn each_stops_on_break() {
+    let mut nodes = Nodes::new(&[(1, 0); 10]);
+    let mut tree = SizeBalancedTree::new();
+    for link in 1..=10 {
+        tree.insert(&mut nodes, link);
+    }
+    let mut visited = 0;
+    let flow = tree.each(&nodes, 1, &mut |_| {
+        visited += 1;
+        if visited == 4 {
+            Flow::Break
+        } else {
+            Flow::Continue
+        }
+    });
+    assert_eq!(flow, Flow::Break);
+    assert_eq!(visited, 4);
+}
+
+#[quickcheck]
+fn agrees_with_linear_scan(keys: Vec<(u8, u8)>, removed: Vec<usize>) -> bool {
+    let keys: Vec<_> = keys.into_iter().map(|(a, b)| (a % 8, b % 4)).collect();
+    let mut nodes = Nodes::new(&keys);
+    let mut tree = SizeBalancedTree::new();
+    let mut present: Vec<u32> = (1..=keys.len() as u32).collect();
+    for &link in &present {
+        tree.insert(&mut nodes, link);
+    }
+    for i in removed {
+        if !present.is_empty() {
+            let link = present.remove(i % present.len());
+            tree.remove(&mut nodes, link);
+        }
+    }
+
+    let key = |link: u32| nodes.keys[link as usize - 1];
+    (0..8).all(|primary| {
+        let mut expected: Vec<_> = present
+            .iter()
+            .copied()
+            .filter(|&link| key(link).0 == primary)
+            .collect();
+        expected.sort_by_key(|&link| (key(link), link));
+
+        collect(&tree, &nodes, primary) == expected
+            && tree.count(&nodes, primary) as usize == expected.len()
+            && (0..4).all(|secondary| {
+                let pairs = expected.iter().filter(|&&link| key(link).1 == secondary);
+                tree.count_pair(&nodes, primary, secondary) as usize == pairs.count()
+            })
+    }) && tree.len(&nodes) as usize == present.len()
+}
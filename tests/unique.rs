@@ -0,0 +1,160 @@
+use platform_data::{
+    DuplicatePolicy, Error, Flow, Link, Links, LinksExt, MemoryLinks, UniqueLinks,
+};
+
+fn unique(policy: DuplicatePolicy) -> UniqueLinks<usize, MemoryLinks<usize>> {
+    UniqueLinks::new(MemoryLinks::new(), policy)
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+
+    // Random sequences create duplicates on purpose, so they are left out.
+    for policy in [DuplicatePolicy::Reject, DuplicatePolicy::Merge] {
+        testing::crud(&mut unique(policy));
+        testing::wildcards(&mut unique(policy));
+        testing::not_exists(&mut unique(policy));
+        testing::already_exists(&mut unique(policy));
+        testing::has_usages(&mut unique(policy));
+        testing::handlers(&mut unique(policy));
+        testing::flow(&mut unique(policy));
+    }
+}
+
+#[test]
+fn placeholders_may_repeat() {
+    let mut links = unique(DuplicatePolicy::Reject);
+    let a = links.create().unwrap();
+    let b = links.create().unwrap();
+    assert_ne!(a, b);
+    assert_eq!(links.count_all(), 2);
+}
+
+#[test]
+fn reject_keeps_storage_unchanged() {
+    let mut links = unique(DuplicatePolicy::Reject);
+    let a = links.create_link(1, 2).unwrap();
+    let b = links.create_link(a, a).unwrap();
+    let before: Vec<_> = links.iter(&[]).unwrap().collect();
+
+    assert!(matches!(
+        links.update(b, 1, 2),
+        Err(Error::AlreadyExists(existing)) if *existing == a
+    ));
+    assert_eq!(links.iter(&[]).unwrap().collect::<Vec<_>>(), before);
+
+    links.update(b, b, b).unwrap();
+    links.update(b, a, a).unwrap();
+}
+
+#[test]
+fn rejected_create_link_leaves_no_placeholder() {
+    let mut links = unique(DuplicatePolicy::Reject);
+    let a = links.create_link(100, 200).unwrap();
+    assert!(matches!(
+        links.create_link(100, 200),
+        Err(Error::AlreadyExists(existing)) if *existing == a
+    ));
+    assert_eq!(links.count_all(), 1);
+}
+
+#[test]
+fn create_with_full_query() {
+    let mut links = unique(DuplicatePolicy::Merge);
+    let any = links.constants_links().any;
+    let mut seen = vec![];
+    links
+        .create_links(&[any, 1, 2], &mut |before, after| {
+            seen.push((before.to_vec(), after.to_vec()));
+            Flow::Continue
+        })
+        .unwrap();
+    assert_eq!(
+        seen,
+        [(vec![], vec![1, 0, 0]), (vec![1, 0, 0], vec![1, 1, 2])]
+    );
+
+    assert!(matches!(
+        links.create_links(&[any, 1, 2], &mut |_, _| Flow::Continue),
+        Err(Error::AlreadyExists(existing)) if *existing == 1
+    ));
+    links
+        .create_links(&[5, 2, 1], &mut |_, _| Flow::Continue)
+        .unwrap();
+    assert_eq!(links.get_link(5), Some(Link::new(5, 2, 1)));
+    assert_eq!(links.count_all(), 2);
+}
+
+#[test]
+fn create_with_any_is_a_plain_create() {
+    for policy in [DuplicatePolicy::Reject, DuplicatePolicy::Merge] {
+        let mut links = unique(policy);
+        let any = links.constants_links().any;
+        for query in [[any, any, any], [any, 100, any], [any, any, any]] {
+            let mut seen = vec![];
+            links
+                .create_links(&query, &mut |before, after| {
+                    seen.push((before.to_vec(), after.to_vec()));
+                    Flow::Continue
+                })
+                .unwrap();
+            let index = links.count_all();
+            assert_eq!(seen, [(vec![], vec![index, 0, 0])]);
+        }
+        assert_eq!(
+            links.iter(&[]).unwrap().collect::<Vec<_>>(),
+            [Link::new(1, 0, 0), Link::new(2, 0, 0), Link::new(3, 0, 0)]
+        );
+    }
+}
+
+#[test]
+fn merge_redirects_usages() {
+    let mut links = unique(DuplicatePolicy::Merge);
+    let a = links.create_link(7, 7).unwrap();
+    let b = links.create_link(8, 8).unwrap();
+    let c = links.create_link(b, 9).unwrap();
+    let d = links.create_link(9, b).unwrap();
+
+    let mut seen = vec![];
+    links
+        .update_links(&[b], &[b, 7, 7], &mut |before, after| {
+            seen.push((before.to_vec(), after.to_vec()));
+            Flow::Continue
+        })
+        .unwrap();
+
+    assert!(!links.exists(b));
+    assert_eq!(links.get_link(c), Some(Link::new(c, a, 9)));
+    assert_eq!(links.get_link(d), Some(Link::new(d, 9, a)));
+    assert_eq!(links.count_all(), 3);
+    assert_eq!(
+        seen,
+        [
+            (vec![c, b, 9], vec![c, a, 9]),
+            (vec![d, 9, b], vec![d, 9, a]),
+            (vec![b, 8, 8], vec![]),
+        ]
+    );
+}
+
+#[test]
+fn merge_cascades_to_usages() {
+    let mut links = unique(DuplicatePolicy::Merge);
+    let a = links.create_link(1, 1).unwrap();
+    let b = links.create_link(1, 2).unwrap();
+    let ab = links.create_link(a, 3).unwrap();
+    let bb = links.create_link(b, 3).unwrap();
+    let top = links.create_link(bb, bb).unwrap();
+
+    links.update(b, 1, 1).unwrap();
+
+    // `bb` became `(a, 3)` and was merged into `ab`, taking `top` along.
+    assert!(!links.exists(b));
+    assert!(!links.exists(bb));
+    assert_eq!(links.get_link(top), Some(Link::new(top, ab, ab)));
+    assert_eq!(links.search(a, 3), Some(ab));
+    assert_eq!(links.count_all(), 3);
+}
@@ -0,0 +1,184 @@
+use platform_data::{DeleteMode, Error, Flow, Link, Links, LinksExt, MemoryLinks, UsageLinks};
+
+fn usage(mode: DeleteMode) -> UsageLinks<usize, MemoryLinks<usize>> {
+    UsageLinks::new(MemoryLinks::new(), mode)
+}
+
+fn deleting(
+    links: &mut UsageLinks<usize, MemoryLinks<usize>>,
+    index: usize,
+) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let mut seen = vec![];
+    links
+        .delete_links(&[index], &mut |before, after| {
+            seen.push((before.to_vec(), after.to_vec()));
+            Flow::Continue
+        })
+        .unwrap();
+    seen
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+
+    testing::run(|| usage(DeleteMode::Restrict));
+
+    // The other modes resolve usages instead of reporting them.
+    for mode in [DeleteMode::Cascade, DeleteMode::SetNull] {
+        testing::crud(&mut usage(mode));
+        testing::wildcards(&mut usage(mode));
+        testing::not_exists(&mut usage(mode));
+        testing::already_exists(&mut usage(mode));
+        testing::handlers(&mut usage(mode));
+        testing::flow(&mut usage(mode));
+    }
+}
+
+#[test]
+fn usages_skip_self_references() {
+    let mut links = MemoryLinks::<usize>::new();
+    let a = links.create().unwrap();
+    links.update(a, a, a).unwrap();
+    let b = links.create_link(a, 900).unwrap();
+    let c = links.create_link(900, a).unwrap();
+    assert_eq!(
+        links.usages(a),
+        [Link::new(b, a, 900), Link::new(c, 900, a)]
+    );
+    assert!(links.usages(b).is_empty());
+}
+
+#[test]
+fn restrict_lists_every_usage() {
+    let mut links = usage(DeleteMode::Restrict);
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create_link(a, 300).unwrap();
+    let c = links.create_link(400, a).unwrap();
+    match links.delete(a) {
+        Err(Error::HasUsages(usages)) => assert_eq!(usages, [vec![b, a, 300], vec![c, 400, a]]),
+        other => panic!("deleted a used link: {:?}", other),
+    }
+    assert_eq!(links.count_all(), 3);
+}
+
+#[test]
+fn set_null_rewrites_references() {
+    let mut links = usage(DeleteMode::SetNull);
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create_link(a, a).unwrap();
+    let c = links.create_link(b, a).unwrap();
+
+    assert_eq!(
+        deleting(&mut links, a),
+        [
+            (vec![b, a, a], vec![b, 0, 0]),
+            (vec![c, b, a], vec![c, b, 0]),
+            (vec![a, 100, 200], vec![]),
+        ]
+    );
+    assert_eq!(links.count_all(), 2);
+}
+
+#[test]
+fn cascade_deletes_dependents_first() {
+    let mut links = usage(DeleteMode::Cascade);
+    let a = links.create_link(100, 200).unwrap();
+    let b = links.create_link(a, 300).unwrap();
+    let c = links.create_link(b, b).unwrap();
+    let d = links.create_link(400, a).unwrap();
+    let other = links.create_link(500, 600).unwrap();
+
+    assert_eq!(
+        deleting(&mut links, a),
+        [
+            (vec![c, b, b], vec![]),
+            (vec![d, 400, a], vec![]),
+            (vec![b, a, 300], vec![]),
+            (vec![a, 100, 200], vec![]),
+        ]
+    );
+    assert_eq!(
+        links.iter(&[]).unwrap().collect::<Vec<_>>(),
+        [Link::new(other, 500, 600)]
+    );
+}
+
+#[test]
+fn cascade_breaks_cycles() {
+    let mut links = usage(DeleteMode::Cascade);
+    let a = links.create().unwrap();
+    let b = links.create_link(a, 900).unwrap();
+    let c = links.create_link(b, 900).unwrap();
+    links.update(a, c, c).unwrap();
+
+    let seen = deleting(&mut links, a);
+    assert_eq!(seen.iter().filter(|(_, after)| after.is_empty()).count(), 3);
+    assert_eq!(links.count_all(), 0);
+}
+
+#[test]
+fn cascade_deletes_cycles_with_dependents() {
+    let mut links = usage(DeleteMode::Cascade);
+    let a = links.create().unwrap();
+    let b = links.create_link(a, a).unwrap();
+    links.update(a, b, a).unwrap();
+    let c = links.create_link(b, 900).unwrap();
+    let d = links.create_link(c, a).unwrap();
+    let other = links.create_link(900, 900).unwrap();
+
+    let seen = deleting(&mut links, c);
+    assert_eq!(seen, [(vec![d, c, a], vec![]), (vec![c, b, 900], vec![])]);
+
+    let seen = deleting(&mut links, b);
+    assert_eq!(seen.iter().filter(|(_, after)| after.is_empty()).count(), 2);
+    assert_eq!(
+        links.iter(&[]).unwrap().collect::<Vec<_>>(),
+        [Link::new(other, 900, 900)]
+    );
+}
+
+#[test]
+fn wildcard_deletes_are_left_to_the_storage() {
+    for mode in [
+        DeleteMode::Restrict,
+        DeleteMode::Cascade,
+        DeleteMode::SetNull,
+    ] {
+        let mut links = usage(mode);
+        let a = links.create_link(100, 200).unwrap();
+        let b = links.create_link(a, a).unwrap();
+        let any = links.constants_links().any;
+        for query in [&[][..], &[any][..]] {
+            assert!(
+                matches!(
+                    links.delete_links(query, &mut |_, _| Flow::Continue),
+                    Err(Error::NotExists(index)) if index == any
+                ),
+                "{:?} deleting {:?}",
+                mode,
+                query
+            );
+        }
+        assert_eq!(
+            links.iter(&[]).unwrap().collect::<Vec<_>>(),
+            [Link::new(a, 100, 200), Link::new(b, a, a)]
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn cascade_over_file_links() {
+    use platform_data::FileLinks;
+    use tempfile::NamedTempFile;
+
+    let file = NamedTempFile::new().unwrap();
+    let storage = FileLinks::<u64>::open(file.path()).unwrap();
+    let mut links = UsageLinks::new(storage, DeleteMode::Cascade);
+    let a = links.create_link(100, 200).unwrap();
+    links.create_link(a, a).unwrap();
+    links.delete(a).unwrap();
+    assert_eq!(links.into_inner().count_all(), 0);
+}
@@ -0,0 +1,131 @@
+use platform_data::{
+    Error, Flow, Links, LinksConstants, LinksExt, MemoryLinks, ValidatedLinks, ValidationError,
+};
+
+fn validated(constants: LinksConstants<u32>) -> ValidatedLinks<u32, MemoryLinks<u32>> {
+    ValidatedLinks::new(MemoryLinks::with_constants(constants))
+}
+
+fn invalid<T>(result: Result<T, Error<'_, u32>>) -> ValidationError<u32> {
+    match result {
+        Err(Error::Invalid(error)) => error,
+        Err(error) => panic!("failed with {:?}", error),
+        Ok(_) => panic!("accepted"),
+    }
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+
+    testing::run(|| ValidatedLinks::new(MemoryLinks::<usize>::new()));
+    testing::run(|| validated(LinksConstants::external()));
+}
+
+#[test]
+fn reserved_constants_are_rejected() {
+    let constants = LinksConstants::<u32>::new();
+    let mut links = validated(constants.clone());
+    let a = links.create().unwrap();
+
+    for value in [
+        constants.skip,
+        constants.error,
+        constants.itself,
+        constants.any,
+    ] {
+        assert_eq!(
+            invalid(links.update(a, value, a)),
+            ValidationError::Reserved { part: 1, value }
+        );
+    }
+    assert_eq!(
+        invalid(links.delete(constants.error)),
+        ValidationError::Reserved {
+            part: 0,
+            value: constants.error
+        }
+    );
+    assert_eq!(links.search(0, 0), Some(a));
+}
+
+#[test]
+fn external_references_need_external_range() {
+    let mut links = validated(LinksConstants::via_ranges(1..=1000, None));
+    let a = links.create().unwrap();
+    assert_eq!(
+        invalid(links.update(a, a, 5000)),
+        ValidationError::Reference {
+            part: 2,
+            value: 5000
+        }
+    );
+    assert_eq!(
+        invalid(links.create_links(&[5000], &mut |_, _| Flow::Continue)),
+        ValidationError::Address {
+            part: 0,
+            value: 5000
+        }
+    );
+
+    let mut links = validated(LinksConstants::via_ranges(1..=1000, Some(2000..=3000)));
+    let a = links.create().unwrap();
+    links.update(a, 2500, a).unwrap();
+    assert_eq!(links.count_links(&[links.constants_links().any, 2500]), 1);
+    assert_eq!(
+        invalid(links.update(a, a, 1500)),
+        ValidationError::Reference {
+            part: 2,
+            value: 1500
+        }
+    );
+    assert_eq!(
+        invalid(links.delete(2500)),
+        ValidationError::External {
+            part: 0,
+            value: 2500
+        }
+    );
+    assert_eq!(
+        invalid(links.delete(1500)),
+        ValidationError::Address {
+            part: 0,
+            value: 1500
+        }
+    );
+}
+
+#[test]
+fn malformed_queries_never_reach_the_storage() {
+    let mut links = validated(LinksConstants::via_ranges(1..=1000, None));
+    let any = links.constants_links().any;
+    let a = links.create_link(1, 1).unwrap();
+    let mut called = false;
+
+    let error = invalid(links.update_links(&[a], &[a, 1], &mut |_, _| {
+        called = true;
+        Flow::Continue
+    }));
+    assert_eq!(error, ValidationError::Length(2));
+    assert_eq!(
+        invalid(links.each_links(&[any, any, any, any], &mut |_| {
+            called = true;
+            Flow::Continue
+        })),
+        ValidationError::Length(4)
+    );
+    assert!(!called);
+
+    assert_eq!(links.count_links(&[any, 5000]), 0);
+    assert_eq!(links.count_links(&[any, 1]), 1);
+    assert_eq!(error.to_string(), "2 parts do not form a link",);
+    assert_eq!(
+        Error::from(ValidationError::External {
+            part: 0,
+            value: 5000u32
+        })
+        .to_string(),
+        "invalid link: part 0 is 5000, which is an external reference, not an internal address"
+    );
+}
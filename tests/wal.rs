@@ -0,0 +1,189 @@
+#![cfg(feature = "std")]
+
+use std::{fs, path::Path};
+
+use platform_data::{
+    Error, FileLinks, Link, LinksExt, MemoryLinks, Recovery, SyncPolicy, WalError, WalLinks,
+};
+use tempfile::TempDir;
+
+/// Length of a commit or abort record.
+const MARKER: u64 = 9;
+
+fn open(path: &Path) -> WalLinks<u64, MemoryLinks<u64>> {
+    WalLinks::open(MemoryLinks::new(), path, SyncPolicy::Always).unwrap()
+}
+
+fn cut(path: &Path, bytes: u64) {
+    let len = fs::metadata(path).unwrap().len();
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_len(len - bytes)
+        .unwrap();
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn conforms() {
+    use platform_data::testing;
+    use std::cell::Cell;
+
+    let dir = TempDir::new().unwrap();
+    let logs = Cell::new(0);
+    testing::run(|| {
+        logs.set(logs.get() + 1);
+        let path = dir.path().join(logs.get().to_string());
+        WalLinks::open(MemoryLinks::<u64>::new(), path, SyncPolicy::Never).unwrap()
+    });
+}
+
+#[test]
+fn recover_rebuilds_memory_links() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("links.wal");
+    let mut links = open(&path);
+    let a = links.create_link(1, 1).unwrap();
+    let b = links.create_link(a, a).unwrap();
+    links.update(a, b, b).unwrap();
+    links.delete(b).unwrap_err();
+    let c = links.create().unwrap();
+    links.delete(c).unwrap();
+    let expected: Vec<_> = links.iter(&[]).unwrap().collect();
+    drop(links);
+
+    let mut links = open(&path);
+    let recovery = links.recover().unwrap();
+    assert_eq!(
+        recovery,
+        Recovery {
+            committed: 7,
+            discarded: 1,
+            repaired: 2
+        }
+    );
+    assert_eq!(links.iter(&[]).unwrap().collect::<Vec<_>>(), expected);
+
+    let recovery = links.recover().unwrap();
+    assert_eq!(recovery.repaired, 0);
+}
+
+#[test]
+fn interrupted_mutation_is_undone() {
+    let dir = TempDir::new().unwrap();
+    let (data, log) = (dir.path().join("links"), dir.path().join("links.wal"));
+    let mut links = WalLinks::open(
+        FileLinks::<u64>::open(&data).unwrap(),
+        &log,
+        SyncPolicy::Always,
+    )
+    .unwrap();
+    let a = links.create().unwrap();
+    links.update(a, a, a).unwrap();
+    links.into_inner().flush().unwrap();
+    cut(&log, MARKER);
+
+    let mut links = WalLinks::open(
+        FileLinks::<u64>::open(&data).unwrap(),
+        &log,
+        SyncPolicy::Always,
+    )
+    .unwrap();
+    assert_eq!(links.get_link(a), Some(Link::new(a, a, a)));
+    let recovery = links.recover().unwrap();
+    assert_eq!(recovery.discarded, 1);
+    assert_eq!(recovery.repaired, 1);
+    assert_eq!(links.get_link(a), Some(Link::new(a, 0, 0)));
+
+    assert_eq!(links.recover().unwrap().discarded, 1);
+}
+
+#[test]
+fn lost_mutations_are_redone() {
+    let dir = TempDir::new().unwrap();
+    let (data, log) = (dir.path().join("links"), dir.path().join("links.wal"));
+    let mut links = WalLinks::open(
+        FileLinks::<u64>::open(&data).unwrap(),
+        &log,
+        SyncPolicy::Always,
+    )
+    .unwrap();
+    let a = links.create().unwrap();
+    links.checkpoint().unwrap();
+    links.update(a, a, a).unwrap();
+    drop(links);
+
+    // The storage lost the update, the log kept it.
+    let mut stale = FileLinks::<u64>::open(&data).unwrap();
+    stale.update(a, 0, 0).unwrap();
+    let mut links = WalLinks::open(stale, &log, SyncPolicy::Always).unwrap();
+    assert_eq!(links.recover().unwrap().repaired, 1);
+    assert_eq!(links.get_link(a), Some(Link::new(a, a, a)));
+}
+
+#[test]
+fn corrupted_tail_is_reported() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("links.wal");
+    let mut links = open(&path);
+    let a = links.create().unwrap();
+    links.update(a, a, a).unwrap();
+    let len = links.log_len().unwrap();
+    drop(links);
+
+    let mut bytes = fs::read(&path).unwrap();
+    let last = bytes.len() - 2;
+    bytes[last] ^= 1;
+    fs::write(&path, bytes).unwrap();
+
+    let mut links = open(&path);
+    let offset = match links.recover() {
+        Err(Error::Wal(WalError::Corrupted(offset))) => offset,
+        other => panic!("recovered {:?}", other),
+    };
+    assert_eq!(offset, len - MARKER);
+    assert_eq!(links.count_all(), 0);
+
+    links.truncate(offset).unwrap();
+    let recovery = links.recover().unwrap();
+    assert_eq!((recovery.committed, recovery.discarded), (1, 1));
+    assert_eq!(links.get_link(a), Some(Link::new(a, 0, 0)));
+}
+
+#[test]
+fn checkpoint_empties_log() {
+    let dir = TempDir::new().unwrap();
+    let (data, log) = (dir.path().join("links"), dir.path().join("links.wal"));
+    let mut links = WalLinks::open(
+        FileLinks::<u64>::open(&data).unwrap(),
+        &log,
+        SyncPolicy::Every(8),
+    )
+    .unwrap();
+    let a = links.create_link(1, 1).unwrap();
+    assert!(links.log_len().unwrap() > 16);
+    links.checkpoint().unwrap();
+    assert_eq!(links.log_len().unwrap(), 16);
+    drop(links);
+
+    let mut links = WalLinks::open(
+        FileLinks::<u64>::open(&data).unwrap(),
+        &log,
+        SyncPolicy::Never,
+    )
+    .unwrap();
+    assert_eq!(links.recover().unwrap(), Recovery::default());
+    assert_eq!(links.get_link(a), Some(Link::new(a, 1, 1)));
+}
+
+#[test]
+fn foreign_file_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("links.wal");
+    fs::write(&path, b"not a write-ahead log").unwrap();
+    assert!(matches!(
+        WalLinks::open(MemoryLinks::<u64>::new(), &path, SyncPolicy::Always),
+        Err(Error::Wal(WalError::Corrupted(0)))
+    ));
+}